@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use piz::raw::CentralDirectoryRecordEnd;
+
+/// Empty archive with a comment of the given length
+fn empty_archive(comment: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 16]);
+    out.extend_from_slice(&(comment.len() as u16).to_le_bytes());
+    out.extend_from_slice(comment);
+    out
+}
+
+/// The window-by-window scan `find` used before, as a baseline
+fn linear_find(bytes: &[u8]) -> Option<usize> {
+    let start_offset = bytes.len().saturating_sub(u16::MAX as usize + 22);
+    return bytes[start_offset..]
+        .windows(22)
+        .rev()
+        .enumerate()
+        .filter(|&(_i, window)| window[..4] == 0x06054b50u32.to_le_bytes())
+        .find(|&(i, window)| u16::from_le_bytes([window[20], window[21]]) as usize == i)
+        .map(|(i, _window)| i);
+}
+
+fn find(c: &mut Criterion) {
+    let long_comment = empty_archive(&[b'x'; u16::MAX as usize]);
+    c.bench_function("find, 64 KiB comment", |b| {
+        b.iter(|| CentralDirectoryRecordEnd::find(black_box(&long_comment)).unwrap())
+    });
+    c.bench_function("linear scan, 64 KiB comment", |b| {
+        b.iter(|| linear_find(black_box(&long_comment)).unwrap())
+    });
+
+    let no_comment = empty_archive(b"");
+    c.bench_function("find, no comment", |b| {
+        b.iter(|| CentralDirectoryRecordEnd::find(black_box(&no_comment)).unwrap())
+    });
+    c.bench_function("linear scan, no comment", |b| {
+        b.iter(|| linear_find(black_box(&no_comment)).unwrap())
+    });
+
+    let mut big = vec![0; 1 << 20];
+    big.extend_from_slice(&empty_archive(b"comment"));
+    c.bench_function("find_within, 1 MiB", |b| {
+        b.iter(|| CentralDirectoryRecordEnd::find_within(black_box(&big), big.len()).unwrap())
+    });
+}
+
+criterion_group!(benches, find);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{Archive, Entry};
+
+fn list(c: &mut Criterion) {
+    let entries = (0..10_000)
+        .map(|i| Entry::stored(&format!("dir/{i}.txt"), &[b'x'; 256]))
+        .collect();
+    let bytes = Archive::new(entries).build();
+
+    c.bench_function("list 10k entries, names and sizes", |b| {
+        b.iter(|| {
+            piz::Zip::new(black_box(&bytes)).unwrap().central_dir_iter
+                .map(|file| file.filename.len() + file.decompressed_size)
+                .sum::<usize>()
+        })
+    });
+
+    c.bench_function("list 10k entries, with data", |b| {
+        b.iter(|| {
+            piz::Zip::new(black_box(&bytes)).unwrap().central_dir_iter
+                .map(|file| file.filename.len() + file.bytes().unwrap().len())
+                .sum::<usize>()
+        })
+    });
+}
+
+criterion_group!(benches, list);
+criterion_main!(benches);
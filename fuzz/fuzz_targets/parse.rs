@@ -0,0 +1,41 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use piz::{OpenOptions, Zip};
+
+fn walk(zip: &Zip<'_>) {
+    for file in zip.files() {
+        let _ = file.bytes();
+        let _ = file.local_header();
+        let _ = file.local_extra_fields();
+        let _ = file.validate_local_header();
+        let _ = file.data_descriptor();
+        let _ = file.decoded_name();
+        let _ = file.preferred_filename();
+        let _ = file.preferred_comment();
+        let _ = file.modified();
+        for _ in file.extra_fields_iter() {}
+        let mut extra = piz::extra::Iter { data: file.extra_fields };
+        while let Ok(Some(_)) = extra.try_next() {}
+        let _ = file.decompress();
+    }
+    for _ in zip.strict_iter() {}
+    let _ = zip.validate_links();
+    let _ = zip.comment();
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(zip) = Zip::new(data) {
+        walk(&zip);
+    }
+    let options = OpenOptions { full_scan: true, allow_prefix: false, ..OpenOptions::default() };
+    if let Some(zip) = Zip::with_options(data, options) {
+        walk(&zip);
+    }
+    for file in piz::local::LocalHeaderIter::new(data) {
+        let _ = file.bytes();
+    }
+    if let Ok(reader) = piz::io::ZipReader::new(std::io::Cursor::new(data)) {
+        for _ in reader.files() {}
+    }
+});
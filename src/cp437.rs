@@ -0,0 +1,37 @@
+//! IBM Code Page 437, the original encoding of ZIP filenames and comments
+//! (used unless general purpose flag bit 11 says UTF-8).
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String};
+
+/// Characters for bytes `0x80..=0xFF`, lower half is plain ASCII
+pub const HIGH_HALF: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+pub const fn decode_byte(byte: u8) -> char {
+    if byte < 0x80 {
+        return byte as char;
+    }
+    return HIGH_HALF[(byte - 0x80) as usize];
+}
+
+/// Decodes CP437 bytes, which never fails, because every byte maps to some character.
+/// Pure ASCII input is borrowed.
+#[cfg(feature = "alloc")]
+pub fn decode(bytes: &[u8]) -> Cow<'_, str> {
+    if let Ok(ascii) = core::str::from_utf8(bytes) {
+        if ascii.is_ascii() {
+            return Cow::Borrowed(ascii);
+        }
+    }
+
+    return Cow::Owned(bytes.iter().copied().map(decode_byte).collect::<String>());
+}
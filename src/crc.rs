@@ -0,0 +1,65 @@
+//! Table-driven CRC-32 implementations.
+
+/// Polynomial of a 32-bit CRC, all of them are reflected with `!0` initial value and final xor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrcPoly {
+    /// Standard CRC-32 used by ZIP (and Ethernet, gzip, PNG...)
+    IsoHdlc,
+    /// CRC-32C, which some non-standard producers put in the CRC fields
+    Castagnoli,
+}
+
+impl CrcPoly {
+    pub const fn reversed(self) -> u32 {
+        return match self {
+            Self::IsoHdlc => 0xEDB88320,
+            Self::Castagnoli => 0x82F63B78,
+        };
+    }
+
+    fn table(self) -> &'static [u32; 256] {
+        return match self {
+            Self::IsoHdlc => &ISO_HDLC_TABLE,
+            Self::Castagnoli => &CASTAGNOLI_TABLE,
+        };
+    }
+
+    pub fn checksum(self, bytes: &[u8]) -> u32 {
+        let table = self.table();
+        let crc = bytes.iter().fold(!0u32, |crc, &byte| {
+            table[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+        });
+        return !crc;
+    }
+}
+
+const fn make_table(poly: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ poly } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    return table;
+}
+
+static ISO_HDLC_TABLE: [u32; 256] = make_table(CrcPoly::IsoHdlc.reversed());
+static CASTAGNOLI_TABLE: [u32; 256] = make_table(CrcPoly::Castagnoli.reversed());
+
+/// Standard CRC-32, the one ZIP uses
+pub fn crc32(bytes: &[u8]) -> u32 {
+    return CrcPoly::IsoHdlc.checksum(bytes);
+}
+
+/// CRC-32C (Castagnoli)
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    return CrcPoly::Castagnoli.checksum(bytes);
+}
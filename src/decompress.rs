@@ -0,0 +1,391 @@
+//! Decompressing whole entries into memory
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{CompressionMethod, File, ValidationError, Zip};
+
+/// Why `File::decompress` failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecompressError {
+    /// Compressed data doesn't fit in the archive
+    DataOutOfBounds,
+    /// Compression method that isn't supported (yet)
+    UnsupportedMethod(CompressionMethod),
+    /// File is encrypted, and decryption isn't supported
+    Encrypted,
+    /// Compressed data is broken
+    Corrupt,
+    /// Decompressed data has a different size than the central directory says
+    SizeMismatch { expected: u64 },
+    /// Decompressed data has a different CRC than the central directory says
+    CrcMismatch { expected: u32, actual: u32 },
+}
+
+impl core::fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::DataOutOfBounds => f.write_str("compressed data out of bounds"),
+            Self::UnsupportedMethod(method) => write!(f, "unsupported compression method {method:?}"),
+            Self::Encrypted => f.write_str("file is encrypted"),
+            Self::Corrupt => f.write_str("corrupt compressed data"),
+            Self::SizeMismatch { expected } => write!(f, "decompressed size differs from expected {expected}"),
+            Self::CrcMismatch { expected, actual } =>
+                write!(f, "CRC of decompressed data is {actual:08x}, expected {expected:08x}"),
+        }
+    }
+}
+
+impl core::error::Error for DecompressError {}
+
+impl<'data> File<'data> {
+    /// Decompresses the whole file into memory, then checks its size and CRC
+    /// against the central directory. This is the verified way to extract,
+    /// the data is only returned if both match.
+    ///
+    /// Supports stored and deflated files, and with the features of the same
+    /// name Deflate64, Zstandard, bzip2, LZMA and XZ.
+    ///
+    /// The output grows as data is decompressed, and never past the declared
+    /// size, so a lying size can't make it allocate much more than the data
+    /// actually decompresses to.
+    pub fn decompress(&self) -> Result<Vec<u8>, DecompressError> {
+        if self.is_encrypted() || self.is_aes_encrypted() {
+            return Err(DecompressError::Encrypted);
+        }
+        let bytes = self.bytes().ok_or(DecompressError::DataOutOfBounds)?;
+        let expected = self.uncompressed_size_u64();
+        let size_mismatch = DecompressError::SizeMismatch { expected };
+        let size = usize::try_from(expected).map_err(|_| size_mismatch)?;
+
+        let data = match self.compression_method {
+            CompressionMethod::Plain => {
+                if bytes.len() != size {
+                    return Err(size_mismatch);
+                }
+                bytes.to_vec()
+            },
+            CompressionMethod::Deflate => inflate(bytes, size)?,
+            #[cfg(feature = "deflate64")]
+            CompressionMethod::Deflate64 => inflate64(bytes, size)?,
+            #[cfg(feature = "zstd")]
+            CompressionMethod::Zstd => unzstd(bytes, size)?,
+            #[cfg(feature = "bzip2")]
+            CompressionMethod::Bzip2 => bunzip2(bytes, size)?,
+            #[cfg(feature = "lzma")]
+            CompressionMethod::Lzma => unlzma(bytes, size, self.flags().has_lzma_end_marker())?,
+            #[cfg(feature = "xz")]
+            CompressionMethod::Xz => unxz(bytes, size)?,
+            method => return Err(DecompressError::UnsupportedMethod(method)),
+        };
+
+        let actual = crate::crc::crc32(&data);
+        if actual != self.decompressed_crc {
+            return Err(DecompressError::CrcMismatch { expected: self.decompressed_crc, actual });
+        }
+
+        return Ok(data);
+    }
+}
+
+impl<'data> Zip<'data> {
+    /// `validate`, and then decompresses every file that passed it to check
+    /// its size and CRC. Files that are encrypted or use a method that isn't
+    /// supported are skipped.
+    pub fn validate_decompressed(&self) -> Result<(), Vec<ValidationError<'data>>> {
+        let mut errors = self.validate().err().unwrap_or_default();
+        for file in self.files() {
+            let filename = file.filename;
+            /* Same record, not just the same name */
+            if errors.iter().any(|error| error.filename().is_some_and(|name| core::ptr::eq(name, filename))) {
+                continue;
+            }
+            match file.decompress() {
+                Ok(_) | Err(DecompressError::Encrypted | DecompressError::UnsupportedMethod(_)) => {},
+                Err(error) => errors.push(ValidationError::Content { filename, error }),
+            }
+        }
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors);
+    }
+
+    /// Every file in the archive together with its decompressed content,
+    /// see `File::decompress`. Encrypted files give `DecompressError::Encrypted`.
+    ///
+    /// This doesn't touch `central_dir_iter`.
+    pub fn extract_iter(&self) -> impl Iterator<Item = Result<(File<'data>, Vec<u8>), DecompressError>> {
+        return self.files().map(|file| {
+            let content = file.decompress()?;
+            return Ok((file, content));
+        });
+    }
+}
+
+/// Decompressed data, growing towards the declared size as it's written.
+///
+/// The declared size comes from the archive, so allocating all of it up front
+/// would let a tiny entry claiming gigabytes abort the process.
+struct Output {
+    buf: Vec<u8>,
+    len: usize,
+    size: usize,
+}
+
+/* Where the buffer starts, it's then doubled as needed */
+const INITIAL_OUTPUT: usize = 64 * 1024;
+
+impl Output {
+    fn new(size: usize, compressed_len: usize) -> Self {
+        let initial = compressed_len.saturating_mul(4).max(INITIAL_OUTPUT).min(size);
+        return Self { buf: vec![0; initial], len: 0, size };
+    }
+
+    fn is_full(&self) -> bool {
+        return self.len == self.size;
+    }
+
+    /// Space for the next chunk, empty only once the declared size is reached
+    fn spare(&mut self) -> &mut [u8] {
+        if self.len == self.buf.len() && !self.is_full() {
+            let new_len = self.len.saturating_mul(2).max(INITIAL_OUTPUT).min(self.size);
+            self.buf.resize(new_len, 0);
+        }
+        return &mut self.buf[self.len..];
+    }
+
+    fn advance(&mut self, written: usize) {
+        self.len += written;
+    }
+
+    fn size_mismatch(&self) -> DecompressError {
+        return DecompressError::SizeMismatch { expected: self.size as u64 };
+    }
+
+    /// The data, if there's exactly as much as declared
+    fn finish(self) -> Result<Vec<u8>, DecompressError> {
+        if !self.is_full() {
+            return Err(self.size_mismatch());
+        }
+        /* Never grows past the declared size, so it's all filled */
+        return Ok(self.buf);
+    }
+}
+
+/// `lzma-rs` writes into it, running out of space past the declared size
+#[cfg(any(feature = "lzma", feature = "xz"))]
+impl std::io::Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        let spare = self.spare();
+        let n = spare.len().min(bytes.len());
+        spare[..n].copy_from_slice(&bytes[..n]);
+        self.advance(n);
+        return Ok(n);
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return Ok(());
+    }
+}
+
+/// Raw deflate stream (without zlib header) of exactly `size` bytes
+fn inflate(bytes: &[u8], size: usize) -> Result<Vec<u8>, DecompressError> {
+    use miniz_oxide::inflate::core::{decompress, inflate_flags, DecompressorOxide};
+    use miniz_oxide::inflate::TINFLStatus;
+
+    /* Back references can reach anywhere in the output, so it's passed whole each time */
+    let flags = inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF;
+    let mut decompressor = alloc::boxed::Box::<DecompressorOxide>::default();
+    let mut out = Output::new(size, bytes.len());
+    let mut input = bytes;
+
+    loop {
+        out.spare();
+        let (status, consumed, written) = decompress(&mut decompressor, input, &mut out.buf, out.len, flags);
+        input = &input[consumed..];
+        out.advance(written);
+        match status {
+            TINFLStatus::Done => return out.finish(),
+            TINFLStatus::HasMoreOutput if !out.is_full() => continue,
+            TINFLStatus::HasMoreOutput => return Err(out.size_mismatch()),
+            _ => return Err(DecompressError::Corrupt),
+        }
+    }
+}
+
+/// Deflate64 stream of exactly `size` bytes.
+///
+/// It's Deflate with a 64 KiB window, distance codes 30 and 31 for the far half
+/// of it, and length code 285 taking 16 extra bits instead of meaning 258,
+/// so a Deflate decoder either rejects it or quietly produces garbage.
+#[cfg(feature = "deflate64")]
+fn inflate64(bytes: &[u8], size: usize) -> Result<Vec<u8>, DecompressError> {
+    /* Has a 256 KiB window inside, so keep it off the stack */
+    let mut inflater = alloc::boxed::Box::new(deflate64::InflaterManaged::new());
+    let mut out = Output::new(size, bytes.len());
+    let mut input = bytes;
+    /* For noticing output past the declared size */
+    let mut past_end = [0; 1];
+
+    while !inflater.finished() {
+        let full = out.is_full();
+        let buf = if full { &mut past_end[..] } else { out.spare() };
+        let result = inflater.inflate(input, buf);
+        if result.data_error {
+            return Err(DecompressError::Corrupt);
+        }
+        if full && result.bytes_written != 0 {
+            return Err(out.size_mismatch());
+        }
+        if result.bytes_consumed == 0 && result.bytes_written == 0 {
+            /* Ran out of input before the end of the stream */
+            return Err(DecompressError::Corrupt);
+        }
+        input = &input[result.bytes_consumed..];
+        out.advance(result.bytes_written);
+    }
+
+    return out.finish();
+}
+
+/// Zstandard frames of exactly `size` bytes
+#[cfg(feature = "zstd")]
+fn unzstd(bytes: &[u8], size: usize) -> Result<Vec<u8>, DecompressError> {
+    use ruzstd::decoding::{BlockDecodingStrategy, FrameDecoder};
+    use ruzstd::decoding::errors::{FrameDecoderError, ReadFrameHeaderError};
+    use ruzstd::io::Read;
+
+    let mut decoder = FrameDecoder::new();
+    let mut out = Output::new(size, bytes.len());
+    let mut input = bytes;
+
+    /* Like `FrameDecoder::decode_all`, but into a growing buffer */
+    while !input.is_empty() {
+        match decoder.init(&mut input) {
+            Ok(()) => {},
+            Err(FrameDecoderError::ReadFrameHeaderError(ReadFrameHeaderError::SkipFrame { length, .. })) => {
+                input = input.get(length as usize..).ok_or(DecompressError::Corrupt)?;
+                continue;
+            },
+            Err(_) => return Err(DecompressError::Corrupt),
+        }
+        loop {
+            decoder.decode_blocks(&mut input, BlockDecodingStrategy::UptoBytes(1024 * 1024))
+                .map_err(|_| DecompressError::Corrupt)?;
+            while decoder.can_collect() != 0 {
+                let spare = out.spare();
+                if spare.is_empty() {
+                    return Err(out.size_mismatch());
+                }
+                let n = decoder.read(spare).map_err(|_| DecompressError::Corrupt)?;
+                if n == 0 {
+                    break;
+                }
+                out.advance(n);
+            }
+            if decoder.is_finished() {
+                break;
+            }
+        }
+    }
+
+    return out.finish();
+}
+
+/// bzip2 stream of exactly `size` bytes
+#[cfg(feature = "bzip2")]
+fn bunzip2(bytes: &[u8], size: usize) -> Result<Vec<u8>, DecompressError> {
+    use bzip2_rs::decoder::{Decoder, ReadState, WriteState};
+
+    let mut out = Output::new(size, bytes.len());
+    let mut input = bytes;
+    let mut decoder = Decoder::new();
+    /* For noticing output past the declared size */
+    let mut past_end = [0; 1];
+    /* The decoder asks for more input forever if the stream is cut short.
+     * A couple of requests without any input left are normal at the end
+     * of the stream, more than that means it's stuck. */
+    let mut idle = 0;
+
+    loop {
+        let full = out.is_full();
+        let buf = if full { &mut past_end[..] } else { out.spare() };
+        match decoder.read(buf).map_err(|_| DecompressError::Corrupt)? {
+            ReadState::Read(_) if full => return Err(out.size_mismatch()),
+            ReadState::Read(n) => {
+                out.advance(n);
+                idle = 0;
+            },
+            ReadState::NeedsWrite(_) => {
+                if input.is_empty() {
+                    idle += 1;
+                    if idle > 3 {
+                        return Err(DecompressError::Corrupt);
+                    }
+                }
+                match decoder.write(input).map_err(|_| DecompressError::Corrupt)? {
+                    WriteState::Written(n) => input = &input[n..],
+                    WriteState::NeedsRead => {},
+                }
+            },
+            ReadState::Eof => break,
+        }
+    }
+
+    return out.finish();
+}
+
+/// Maps errors of `lzma-rs`, which writes into an `Output`,
+/// so running out of space there means there's more data than declared
+#[cfg(any(feature = "lzma", feature = "xz"))]
+fn lzma_error(error: lzma_rs::error::Error, size: usize) -> DecompressError {
+    return match error {
+        lzma_rs::error::Error::IoError(e) if e.kind() == std::io::ErrorKind::WriteZero =>
+            DecompressError::SizeMismatch { expected: size as u64 },
+        _ => DecompressError::Corrupt,
+    };
+}
+
+/// LZMA stream in the ZIP flavour, of exactly `size` bytes.
+///
+/// This is not a `.lzma` file: instead of 5 bytes of properties followed by
+/// 8 bytes of decompressed size, ZIP has 2 bytes of LZMA SDK version,
+/// 2 bytes of properties length (always 5) and then the properties. The size
+/// is only in the headers, and whether the stream ends with an end-of-stream
+/// marker is general purpose flag bit 1. So the first 4 bytes are dropped and
+/// the decoder is told to not look for the size after the properties.
+#[cfg(feature = "lzma")]
+fn unlzma(bytes: &[u8], size: usize, end_marker: bool) -> Result<Vec<u8>, DecompressError> {
+    use lzma_rs::decompress::{Options, UnpackedSize};
+
+    let (header, mut stream) = crate::slice_split_at(bytes, 4).ok_or(DecompressError::Corrupt)?;
+    if header[2..] != [5, 0] {
+        return Err(DecompressError::Corrupt);
+    }
+
+    /* With the marker, decoding until it also catches declared size being too big */
+    let unpacked_size = if end_marker { None } else { Some(size as u64) };
+    let options = Options {
+        unpacked_size: UnpackedSize::UseProvided(unpacked_size),
+        ..Options::default()
+    };
+
+    let mut out = Output::new(size, bytes.len());
+    lzma_rs::lzma_decompress_with_options(&mut stream, &mut out, &options)
+        .map_err(|e| lzma_error(e, size))?;
+
+    return out.finish();
+}
+
+/// XZ stream of exactly `size` bytes, which unlike LZMA is stored in ZIP as is
+#[cfg(feature = "xz")]
+fn unxz(bytes: &[u8], size: usize) -> Result<Vec<u8>, DecompressError> {
+    let mut stream = bytes;
+    let mut out = Output::new(size, bytes.len());
+    lzma_rs::xz_decompress(&mut stream, &mut out).map_err(|e| lzma_error(e, size))?;
+
+    return out.finish();
+}
@@ -0,0 +1,56 @@
+use crate::raw;
+
+/// Data descriptor, which trails the compressed data of an entry when bit 3 of
+/// the general purpose flags is set (streaming producers don't know the sizes
+/// and the CRC before they finish writing the data).
+///
+/// The `0x08074b50` signature is optional and both sizes are 8 bytes wide
+/// for Zip64 entries, so the descriptor can be 12, 16, 20 or 24 bytes long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataDescriptor {
+    pub crc: u32,
+    pub compressed_size: u64,
+    pub decompressed_size: u64,
+    /// Length of the whole descriptor in bytes, including the signature
+    pub len: usize,
+}
+
+impl DataDescriptor {
+    /// Parses descriptor from the beginning of `bytes`.
+    /// `zip64` decides whether the sizes are 4 or 8 bytes wide - it can't be
+    /// guessed from the descriptor itself, see `File::is_zip64`.
+    pub fn parse(bytes: &[u8], zip64: bool) -> Option<Self> {
+        let (signature_len, bytes) = match bytes {
+            [a, b, c, d, tail @ ..] if u32::from_le_bytes([*a, *b, *c, *d]) == raw::DATA_DESCRIPTOR_SIGNATURE => (4, tail),
+            _ => (0, bytes),
+        };
+
+        let (crc, bytes) = crate::slice_split_at(bytes, 4)?;
+        let crc = u32::from_le_bytes(crc.try_into().ok()?);
+
+        let (compressed_size, decompressed_size, sizes_len) = if zip64 {
+            let (compressed_size, bytes) = crate::slice_split_at(bytes, 8)?;
+            let (decompressed_size, _bytes) = crate::slice_split_at(bytes, 8)?;
+            (
+                u64::from_le_bytes(compressed_size.try_into().ok()?),
+                u64::from_le_bytes(decompressed_size.try_into().ok()?),
+                16,
+            )
+        } else {
+            let (compressed_size, bytes) = crate::slice_split_at(bytes, 4)?;
+            let (decompressed_size, _bytes) = crate::slice_split_at(bytes, 4)?;
+            (
+                u32::from_le_bytes(compressed_size.try_into().ok()?) as u64,
+                u32::from_le_bytes(decompressed_size.try_into().ok()?) as u64,
+                8,
+            )
+        };
+
+        Some(Self {
+            crc,
+            compressed_size,
+            decompressed_size,
+            len: signature_len + 4 + sizes_len,
+        })
+    }
+}
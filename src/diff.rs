@@ -0,0 +1,78 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::Zip;
+
+/// Differences between entry sets of two archives, by filename.
+/// Each list is in the order of the central directory it comes from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveDiff<'a, 'b> {
+    /// Names present only in the other archive
+    pub added: Vec<&'b [u8]>,
+    /// Names present only in this archive
+    pub removed: Vec<&'a [u8]>,
+    /// Names present in both, but with different CRC or sizes
+    pub changed: Vec<&'a [u8]>,
+}
+
+impl<'a, 'b> ArchiveDiff<'a, 'b> {
+    pub fn is_empty(&self) -> bool {
+        return self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty();
+    }
+}
+
+/// What we compare to decide whether an entry changed
+#[derive(PartialEq, Eq)]
+struct Fingerprint {
+    crc: u32,
+    compressed_size: u64,
+    decompressed_size: u64,
+}
+
+impl<'data> Zip<'data> {
+    /// Compares entries of two archives, using only the central directories.
+    /// If a name appears more than once, the last entry with it counts,
+    /// and is the one whose position decides the order.
+    pub fn diff<'b>(&self, other: &Zip<'b>) -> ArchiveDiff<'data, 'b> {
+        /* Later records overwrite earlier ones, so each name maps to its last record */
+        let ours: BTreeMap<&[u8], (usize, Fingerprint)> = self.records()
+            .enumerate()
+            .map(|(index, record)| (record.filename, (index, record.fingerprint())))
+            .collect();
+        let theirs: BTreeMap<&[u8], (usize, Fingerprint)> = other.records()
+            .enumerate()
+            .map(|(index, record)| (record.filename, (index, record.fingerprint())))
+            .collect();
+
+        let mut diff = ArchiveDiff::default();
+
+        for (index, record) in self.records().enumerate() {
+            if ours[record.filename].0 != index {
+                continue;
+            }
+            match theirs.get(record.filename) {
+                None => diff.removed.push(record.filename),
+                Some((_, fingerprint)) if *fingerprint != record.fingerprint() => diff.changed.push(record.filename),
+                Some(_) => {},
+            }
+        }
+
+        for (index, record) in other.records().enumerate() {
+            if theirs[record.filename].0 == index && !ours.contains_key(record.filename) {
+                diff.added.push(record.filename);
+            }
+        }
+
+        return diff;
+    }
+}
+
+impl<'a> crate::CentralRecord<'a> {
+    fn fingerprint(&self) -> Fingerprint {
+        return Fingerprint {
+            crc: self.header.decompressed_crc.get(),
+            compressed_size: self.compressed_size,
+            decompressed_size: self.decompressed_size,
+        };
+    }
+}
@@ -4,26 +4,89 @@ pub struct Iter<'extra> {
     pub data: &'extra [u8],
 }
 
+impl<'extra> Iter<'extra> {
+    /// Like `next`, but tells a malformed field apart from the end of fields.
+    /// After an error there are no more fields.
+    pub fn try_next(&mut self) -> Result<Option<(u16, &'extra [u8])>, MalformedExtra> {
+        let Some((header, tail)) = crate::slice_split_at(self.data, 4) else {
+            let len = self.data.len();
+            self.data = &[];
+            if len == 0 {
+                return Ok(None);
+            }
+            return Err(MalformedExtra::TruncatedHeader { len });
+        };
+
+        let signature = u16::from_le_bytes([header[0], header[1]]);
+        let declared = u16::from_le_bytes([header[2], header[3]]);
+        let Some((extra, tail)) = crate::slice_split_at(tail, declared as usize) else {
+            self.data = &[];
+            return Err(MalformedExtra::LengthExceedsData { signature, declared, available: tail.len() });
+        };
+        self.data = tail;
+        return Ok(Some((signature, extra)));
+    }
+}
+
 impl<'extra> Iterator for Iter<'extra> {
     type Item = (u16, &'extra [u8]);
     fn next(&mut self) -> Option<Self::Item> {
-        if let [signature_low, signature_high, len_low, len_high, tail @ ..] = self.data {
-            let signature = u16::from_le_bytes([*signature_low, *signature_high]);
-            let len = u16::from_le_bytes([*len_low, *len_high]) as usize;
+        return self.try_next().ok().flatten();
+    }
+}
 
-            let (extra, tail) = crate::slice_split_at(tail, len)?;
-            self.data = tail;
-            return Some((signature, extra));
-        }
+/// Why `Iter::try_next` couldn't read an extra field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MalformedExtra {
+    /// Fewer than 4 bytes left, not enough for a field header
+    TruncatedHeader { len: usize },
+    /// Field claims to be longer than what's left
+    LengthExceedsData { signature: u16, declared: u16, available: usize },
+}
 
-        return None;
+impl core::fmt::Display for MalformedExtra {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TruncatedHeader { len } => write!(f, "{len} trailing bytes are too short for an extra field header"),
+            Self::LengthExceedsData { signature, declared, available } =>
+                write!(f, "extra field {signature:#06x} is {declared} bytes long, but only {available} bytes are left"),
+        }
     }
 }
 
-pub trait Extra: Sized {
+impl core::error::Error for MalformedExtra {}
+
+/// Signatures of the extra fields this module can parse
+pub const KNOWN_SIGNATURES: &[u16] = &[
+    Zip64::SIGNATURE,
+    ExtendedTimestamp::SIGNATURE,
+    NtfsTimestamps::SIGNATURE,
+    UnixUidGid::SIGNATURE,
+    UnixOld::SIGNATURE,
+    UnicodePath::SIGNATURE,
+    UnicodeComment::SIGNATURE,
+    AesExtra::SIGNATURE,
+];
+
+/// First field of type `E` in `data`, if there's one and it parses
+pub fn find<'extra, E: Extra<'extra>>(data: &'extra [u8]) -> Option<E> {
+    let (_, field) = Iter { data }.find(|&(signature, _)| signature == E::SIGNATURE)?;
+    return E::parse(field);
+}
+
+/// Extra fields this module can't parse, like the PKWARE-reserved ones
+/// (AV Info `0x0007`, extended language `0x0008`...) or vendor ones,
+/// as opaque `(signature, data)` pairs
+pub fn unknown(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    return Iter { data }.filter(|(signature, _)| !KNOWN_SIGNATURES.contains(signature));
+}
+
+/// Extra field type, which may borrow from the data it's parsed from
+pub trait Extra<'extra>: Sized {
     const SIGNATURE: u16;
 
-    fn parse(bytes: &[u8]) -> Option<Self>;
+    fn parse(bytes: &'extra [u8]) -> Option<Self>;
 }
 
 pub struct Zip64 {
@@ -33,18 +96,307 @@ pub struct Zip64 {
     pub disk_no: u32,
 }
 
-impl Extra for Zip64 {
+impl Extra<'_> for Zip64 {
     const SIGNATURE: u16 = 0x0001;
 
+    /// Parses the field assuming that all values are present, except the disk
+    /// number, which is often omitted (it is 0 then).
+    /// Use `parse_with_header` when the header is available.
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut bytes = bytes;
+
+        Some(Self {
+            decompressed_size: crate::take_u64(&mut bytes)?,
+            compressed_size: crate::take_u64(&mut bytes)?,
+            local_header_record_offset: crate::take_u64(&mut bytes)?,
+            disk_no: crate::take_u32(&mut bytes).unwrap_or(0),
+        })
+    }
+}
+
+impl Zip64 {
+    /// Parses the field the way it is actually laid out - each value is present
+    /// only if its counterpart in the central directory header is saturated
+    /// (`0xFFFFFFFF`, or `0xFFFF` for the disk number), in order.
+    /// Values that aren't present are taken from the header.
+    pub fn parse_with_header(bytes: &[u8], header: &raw::CentralDirectoryFileHeader) -> Option<Self> {
+        let mut bytes = bytes;
+
+        let decompressed_size = match header.decompressed_size.get() {
+            u32::MAX => crate::take_u64(&mut bytes)?,
+            x => x as u64,
+        };
+        let compressed_size = match header.compressed_size.get() {
+            u32::MAX => crate::take_u64(&mut bytes)?,
+            x => x as u64,
+        };
+        let local_header_record_offset = match header.local_file_header_offset.get() {
+            u32::MAX => crate::take_u64(&mut bytes)?,
+            x => x as u64,
+        };
+        let disk_no = match header.disk_number.get() {
+            u16::MAX => crate::take_u32(&mut bytes)?,
+            x => x as u32,
+        };
+
+        Some(Self {
+            decompressed_size,
+            compressed_size,
+            local_header_record_offset,
+            disk_no,
+        })
+    }
+}
+
+/// Info-ZIP extended timestamp, Unix times in seconds.
+///
+/// `flags` says which times the local header version of the field carries,
+/// but the central directory version has only the modification time, even
+/// if the flags say otherwise. Times that aren't there are `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedTimestamp {
+    pub flags: u8,
+    pub modified: Option<i32>,
+    pub accessed: Option<i32>,
+    pub created: Option<i32>,
+}
+
+impl ExtendedTimestamp {
+    pub const MODIFIED: u8 = 1 << 0;
+    pub const ACCESSED: u8 = 1 << 1;
+    pub const CREATED: u8 = 1 << 2;
+}
+
+impl Extra<'_> for ExtendedTimestamp {
+    const SIGNATURE: u16 = 0x5455;
+
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let (&flags, mut bytes) = bytes.split_first()?;
+        let mut take = |flag: u8| {
+            if flags & flag == 0 {
+                return None;
+            }
+            return crate::take_u32(&mut bytes).map(|x| x as i32);
+        };
+
+        Some(Self {
+            flags,
+            modified: take(Self::MODIFIED),
+            accessed: take(Self::ACCESSED),
+            created: take(Self::CREATED),
+        })
+    }
+}
+
+/// NTFS times as FILETIME values, 100-nanosecond intervals since 1601-01-01
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NtfsTimestamps {
+    pub modified: u64,
+    pub accessed: u64,
+    pub created: u64,
+}
+
+impl NtfsTimestamps {
+    /// FILETIME of the Unix epoch
+    const UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+    /// Converts a FILETIME to Unix time in seconds, rounding towards negative infinity
+    pub const fn to_unix(filetime: u64) -> i64 {
+        let diff = filetime as i128 - Self::UNIX_EPOCH as i128;
+        return diff.div_euclid(10_000_000) as i64;
+    }
+}
+
+impl Extra<'_> for NtfsTimestamps {
+    const SIGNATURE: u16 = 0x000A;
+
+    /// Skips the reserved field and looks for the times attribute (tag `0x0001`)
+    /// among the tag/size subfields
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let (_reserved, bytes) = crate::slice_split_at(bytes, 4)?;
+        let (_, mut times) = Iter { data: bytes }.find(|&(tag, _)| tag == 0x0001)?;
+
+        Some(Self {
+            modified: crate::take_u64(&mut times)?,
+            accessed: crate::take_u64(&mut times)?,
+            created: crate::take_u64(&mut times)?,
+        })
+    }
+}
+
+/// Info-ZIP "new Unix" field with owner's uid and gid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnixUidGid {
+    pub version: u8,
+    pub uid: u64,
+    pub gid: u64,
+}
+
+impl UnixUidGid {
+    /// Little-endian value prefixed with its size in bytes, at most 8
+    fn take_sized(bytes: &mut &[u8]) -> Option<u64> {
+        let (&size, tail) = bytes.split_first()?;
+        if size > 8 {
+            return None;
+        }
+        let (value, tail) = crate::slice_split_at(tail, size as usize)?;
+        *bytes = tail;
+
+        let mut buf = [0; 8];
+        buf[..value.len()].copy_from_slice(value);
+        return Some(u64::from_le_bytes(buf));
+    }
+}
+
+impl Extra<'_> for UnixUidGid {
+    const SIGNATURE: u16 = 0x7875;
+
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let (&version, mut bytes) = bytes.split_first()?;
+
+        Some(Self {
+            version,
+            uid: Self::take_sized(&mut bytes)?,
+            gid: Self::take_sized(&mut bytes)?,
+        })
+    }
+}
+
+/// Info-ZIP "old Unix" field, superseded by `ExtendedTimestamp` and `UnixUidGid`.
+/// Times are seconds since the Unix epoch. Owner is only in the local header version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnixOld {
+    pub accessed: i32,
+    pub modified: i32,
+    pub uid: Option<u16>,
+    pub gid: Option<u16>,
+}
+
+impl Extra<'_> for UnixOld {
+    const SIGNATURE: u16 = 0x5855;
+
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut bytes = bytes;
+        let accessed = crate::take_u32(&mut bytes)? as i32;
+        let modified = crate::take_u32(&mut bytes)? as i32;
+        let owner = bytes.get(..4)
+            .map(|owner| (u16::from_le_bytes([owner[0], owner[1]]), u16::from_le_bytes([owner[2], owner[3]])));
+
+        Some(Self {
+            accessed,
+            modified,
+            uid: owner.map(|(uid, _)| uid),
+            gid: owner.map(|(_, gid)| gid),
+        })
+    }
+}
+
+/// Info-ZIP Unicode path, UTF-8 version of a filename which couldn't be
+/// stored in the base encoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnicodePath<'extra> {
+    pub version: u8,
+    /// CRC-32 of the filename from the header this field belongs to
+    pub name_crc: u32,
+    pub name: &'extra [u8],
+}
+
+impl UnicodePath<'_> {
+    /// Whether this field was made for `filename`. If it wasn't, the filename
+    /// was changed by a tool unaware of this field and this one is stale.
+    pub fn matches(&self, filename: &[u8]) -> bool {
+        return crate::crc::crc32(filename) == self.name_crc;
+    }
+}
+
+impl<'extra> Extra<'extra> for UnicodePath<'extra> {
+    const SIGNATURE: u16 = 0x7075;
+
+    fn parse(bytes: &'extra [u8]) -> Option<Self> {
+        let (&version, mut bytes) = bytes.split_first()?;
+        let name_crc = crate::take_u32(&mut bytes)?;
+
+        Some(Self {
+            version,
+            name_crc,
+            name: bytes,
+        })
+    }
+}
+
+/// Info-ZIP Unicode comment, UTF-8 version of a file comment, like `UnicodePath`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnicodeComment<'extra> {
+    pub version: u8,
+    /// CRC-32 of the comment from the header this field belongs to
+    pub comment_crc: u32,
+    pub comment: &'extra [u8],
+}
+
+impl UnicodeComment<'_> {
+    /// Whether this field was made for `comment`, see `UnicodePath::matches`
+    pub fn matches(&self, comment: &[u8]) -> bool {
+        return crate::crc::crc32(comment) == self.comment_crc;
+    }
+}
+
+impl<'extra> Extra<'extra> for UnicodeComment<'extra> {
+    const SIGNATURE: u16 = 0x6375;
+
+    fn parse(bytes: &'extra [u8]) -> Option<Self> {
+        let (&version, mut bytes) = bytes.split_first()?;
+        let comment_crc = crate::take_u32(&mut bytes)?;
+
+        Some(Self {
+            version,
+            comment_crc,
+            comment: bytes,
+        })
+    }
+}
+
+/// WinZip AES encryption field. Headers of AES-encrypted entries have
+/// compression method 99, the actual one is stored here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AesExtra {
+    /// 1 for AE-1, 2 for AE-2 (which has no CRC)
+    pub version: u16,
+    /// Always `AE`
+    pub vendor: [u8; 2],
+    /// 1, 2 or 3 for 128, 192 or 256-bit keys
+    pub strength: u8,
+    pub compression_method: u16,
+}
+
+impl AesExtra {
+    /// Key size in bits, `None` for unknown strength
+    pub const fn key_bits(&self) -> Option<u16> {
+        return match self.strength {
+            1 => Some(128),
+            2 => Some(192),
+            3 => Some(256),
+            _ => None,
+        };
+    }
+
+    pub const fn actual_compression_method(&self) -> Option<crate::CompressionMethod> {
+        return crate::CompressionMethod::from_u16(self.compression_method);
+    }
+}
+
+impl Extra<'_> for AesExtra {
+    const SIGNATURE: u16 = 0x9901;
+
     fn parse(bytes: &[u8]) -> Option<Self> {
-        let bytes = bytes.get(.. core::mem::size_of::<raw::Zip64Extra>())?;
-        let raw = unsafe { &*(bytes.as_ptr() as *const raw::Zip64Extra) };
+        let &[v0, v1, vendor0, vendor1, strength, m0, m1] = bytes else {
+            return None;
+        };
 
         Some(Self {
-            decompressed_size: raw.decompressed_size,
-            compressed_size: raw.compressed_size,
-            local_header_record_offset: raw.local_header_record_offset,
-            disk_no: raw.disk_no,
+            version: u16::from_le_bytes([v0, v1]),
+            vendor: [vendor0, vendor1],
+            strength,
+            compression_method: u16::from_le_bytes([m0, m1]),
         })
     }
 }
@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+
+use crate::{CentralRecord, File, Zip};
+
+/// Archive with precomputed offsets of central directory records,
+/// for random access by index without walking the directory every time.
+pub struct IndexedZip<'data> {
+    zip: Zip<'data>,
+    /// Offset of every central directory record, in order
+    offsets: Vec<usize>,
+}
+
+impl<'data> IndexedZip<'data> {
+    /// Walks the central directory once, up to the declared number of records
+    /// or the first record that doesn't fit in the buffer
+    pub fn new(zip: Zip<'data>) -> Self {
+        let mut offset = zip.central_dir_offset;
+        let offsets = zip.records()
+            .map(|record| {
+                let start = offset;
+                offset += record.len;
+                return start;
+            })
+            .collect();
+
+        return Self { zip, offsets };
+    }
+
+    pub fn zip(&self) -> &Zip<'data> {
+        return &self.zip;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.offsets.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.offsets.is_empty();
+    }
+
+    /// The `index`-th file in the central directory, in O(1)
+    pub fn get_index(&self, index: usize) -> Option<File<'data>> {
+        let offset = *self.offsets.get(index)?;
+        let record = CentralRecord::parse(&self.zip.data[offset..])?;
+        return record.into_file(self.zip.data, self.zip.name_encoding);
+    }
+}
@@ -0,0 +1,348 @@
+//! `std::io` adapters over entries.
+
+use core::mem;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::vec::Vec;
+#[cfg(feature = "decompress")]
+use std::boxed::Box;
+
+use crate::{raw, CompressionMethod, File, NameEncoding, NonStrictIter, Zip, ZipError};
+
+/// Reader with seeking over the decompressed content of a single entry,
+/// see `File::seekable_reader`
+pub struct SeekableReader<'data> {
+    inner: Inner<'data>,
+}
+
+enum Inner<'data> {
+    /// Stored data is the content itself, so this is free
+    Stored(Cursor<&'data [u8]>),
+    /// Whole content decompressed up front
+    #[cfg(feature = "decompress")]
+    Decompressed(Cursor<Vec<u8>>),
+}
+
+/// Streaming reader over the decompressed content of a single entry,
+/// see `File::reader`
+pub struct Reader<'data> {
+    inner: ReaderInner<'data>,
+}
+
+enum ReaderInner<'data> {
+    Stored(&'data [u8]),
+    #[cfg(feature = "decompress")]
+    Deflate {
+        input: &'data [u8],
+        state: Box<miniz_oxide::inflate::stream::InflateState>,
+        finished: bool,
+    },
+}
+
+impl<'data> File<'data> {
+    /// Reader streaming the decompressed content, without holding all of it
+    /// in memory at once. Deflate needs the `decompress` feature.
+    ///
+    /// Unlike `decompress`, this doesn't check the size or CRC.
+    /// Encrypted files, including strongly encrypted ones, aren't supported.
+    pub fn reader(&self) -> io::Result<Reader<'data>> {
+        self.check_not_encrypted()?;
+        let bytes = self.bytes()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "entry data out of bounds"))?;
+
+        let inner = match self.compression_method {
+            CompressionMethod::Plain => ReaderInner::Stored(bytes),
+            #[cfg(feature = "decompress")]
+            CompressionMethod::Deflate => ReaderInner::Deflate {
+                input: bytes,
+                state: miniz_oxide::inflate::stream::InflateState::new_boxed(miniz_oxide::DataFormat::Raw),
+                finished: false,
+            },
+            _ => return Err(io::Error::new(io::ErrorKind::Unsupported, "unsupported compression method")),
+        };
+
+        return Ok(Reader { inner });
+    }
+
+    /// Reader over the decompressed content that supports seeking,
+    /// for using zipped files as random access storage.
+    ///
+    /// Stored entries are read straight from the archive buffer,
+    /// so seeking is O(1). With the `decompress` feature, other methods it
+    /// supports are decompressed into memory here, otherwise they're not supported.
+    pub fn seekable_reader(self) -> io::Result<SeekableReader<'data>> {
+        self.check_not_encrypted()?;
+        let bytes = self.bytes()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "entry data out of bounds"))?;
+
+        let inner = match self.compression_method {
+            CompressionMethod::Plain => Inner::Stored(Cursor::new(bytes)),
+            #[cfg(feature = "decompress")]
+            _ => Inner::Decompressed(Cursor::new(self.decompress().map_err(|err| match err {
+                crate::decompress::DecompressError::UnsupportedMethod(_) =>
+                    io::Error::new(io::ErrorKind::Unsupported, err),
+                _ => io::Error::new(io::ErrorKind::InvalidData, err),
+            })?)),
+            #[cfg(not(feature = "decompress"))]
+            _ => return Err(io::Error::new(io::ErrorKind::Unsupported, "unsupported compression method")),
+        };
+
+        return Ok(SeekableReader { inner });
+    }
+
+    /* Stored data would be handed out as ciphertext otherwise */
+    fn check_not_encrypted(&self) -> io::Result<()> {
+        if self.is_encrypted() || self.is_aes_encrypted() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "file is encrypted"));
+        }
+        return Ok(());
+    }
+}
+
+impl<'data> Read for SeekableReader<'data> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Stored(cursor) => cursor.read(buf),
+            #[cfg(feature = "decompress")]
+            Inner::Decompressed(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl<'data> Seek for SeekableReader<'data> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &mut self.inner {
+            Inner::Stored(cursor) => cursor.seek(pos),
+            #[cfg(feature = "decompress")]
+            Inner::Decompressed(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
+impl<'data> Read for Reader<'data> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            ReaderInner::Stored(bytes) => bytes.read(buf),
+            #[cfg(feature = "decompress")]
+            ReaderInner::Deflate { input, state, finished } => {
+                use miniz_oxide::{MZError, MZFlush, MZStatus};
+
+                if *finished || buf.is_empty() {
+                    return Ok(0);
+                }
+
+                /* Some calls only consume input (like block headers) without
+                 * producing anything, and returning 0 would mean the end */
+                loop {
+                    let result = miniz_oxide::inflate::stream::inflate(state, input, buf, MZFlush::None);
+                    *input = &input[result.bytes_consumed..];
+
+                    match result.status {
+                        Ok(MZStatus::StreamEnd) => *finished = true,
+                        Ok(_) | Err(MZError::Buf) => {},
+                        Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt deflate stream")),
+                    }
+                    if result.bytes_written > 0 || *finished {
+                        return Ok(result.bytes_written);
+                    }
+                    if result.bytes_consumed == 0 {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated deflate stream"));
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Archive read through `Read + Seek` instead of from a buffer, for archives
+/// too big to load whole. Only the central directory is kept in memory.
+///
+/// Files it yields have no access to the archive, so `File::bytes` and
+/// others reading the data return `None`, use `entry_reader` instead.
+/// Both go through the central directory, so the indices match.
+pub struct ZipReader<R> {
+    reader: R,
+    /// Length of the data before the archive, see `Zip::prefix_len`
+    prefix_len: u64,
+    /// Where `central_dir` was read from, relative to the start of the archive
+    central_dir_offset: usize,
+    central_dir: Vec<u8>,
+    pub central_dir_records_total: u64,
+    name_encoding: NameEncoding,
+}
+
+impl<R: Read + Seek> ZipReader<R> {
+    /// Finds the end record in the last 64 KiB and reads the central directory,
+    /// like `Zip::new`. Errors from parsing are `InvalidData` wrapping a `ZipError`.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        const END_RECORD_SIZE: usize = mem::size_of::<raw::CentralDirectoryRecordEnd>();
+        const LOCATOR_SIZE: usize = mem::size_of::<raw::Zip64CentralDirectoryLocator>();
+        const ZIP64_END_RECORD_SIZE: usize = mem::size_of::<raw::Zip64CentralDirectoryRecordEnd>();
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < END_RECORD_SIZE as u64 {
+            return Err(invalid(ZipError::BufferTooSmall { len: len as usize }));
+        }
+
+        /* Enough for the longest comment, plus Zip64 records that usually precede the end record */
+        let tail_start = len.saturating_sub((u16::MAX as usize + END_RECORD_SIZE + LOCATOR_SIZE + ZIP64_END_RECORD_SIZE) as u64);
+        let mut tail = std::vec![0; (len - tail_start) as usize];
+        reader.seek(SeekFrom::Start(tail_start))?;
+        reader.read_exact(&mut tail)?;
+
+        let (header, _) = raw::CentralDirectoryRecordEnd::find(&tail)
+            .ok_or(invalid(ZipError::EndRecordNotFound))?;
+        let end_record_offset = header as *const _ as usize - tail.as_ptr() as usize;
+
+        let mut central_dir_records_total = header.central_dir_records_total.get() as u64;
+        let mut central_dir_offset = header.central_dir_offset.get() as u64;
+        let mut central_dir_size = header.central_dir_size.get() as u64;
+        let mut central_dir_end = tail_start + end_record_offset as u64;
+        let mut disk = header.disk_num.get() as u32;
+        let mut central_dir_start_disk = header.central_dir_start_disk.get() as u32;
+
+        if header.needs_zip64() {
+            let locator = raw::Zip64CentralDirectoryLocator::find(&tail, end_record_offset)
+                .ok_or(invalid(ZipError::Zip64LocatorMissing))?;
+            let locator_pos = central_dir_end - LOCATOR_SIZE as u64;
+
+            /* Same fallback for prepended data as `Zip::new` */
+            let mut buf = [0; ZIP64_END_RECORD_SIZE];
+            let mut record_pos = None;
+            for pos in [Some(locator.central_dir_end_offset.get()), locator_pos.checked_sub(ZIP64_END_RECORD_SIZE as u64)] {
+                let Some(pos) = pos else { continue };
+                if read_at(&mut reader, len, pos, &mut buf)? && raw::Zip64CentralDirectoryRecordEnd::at(&buf, 0).is_some() {
+                    record_pos = Some(pos);
+                    break;
+                }
+            }
+            let record_pos = record_pos.ok_or(invalid(ZipError::Zip64EndRecordNotFound))?;
+            let record = raw::Zip64CentralDirectoryRecordEnd::at(&buf, 0)
+                .ok_or(invalid(ZipError::Zip64EndRecordNotFound))?;
+
+            if header.central_dir_records_total.get() == u16::MAX {
+                central_dir_records_total = record.central_dir_records_total.get();
+            }
+            if header.central_dir_offset.get() == u32::MAX {
+                central_dir_offset = record.central_dir_offset.get();
+            }
+            if header.central_dir_size.get() == u32::MAX {
+                central_dir_size = record.central_directory_size.get();
+            }
+            if header.disk_num.get() == u16::MAX {
+                disk = record.disk_num.get();
+            }
+            if header.central_dir_start_disk.get() == u16::MAX {
+                central_dir_start_disk = record.central_dir_start_disk.get();
+            }
+            central_dir_end = record_pos;
+        }
+
+        if disk != 0 || central_dir_start_disk != 0 {
+            return Err(invalid(ZipError::SplitArchive { disk, central_dir_start_disk }));
+        }
+
+        /* See `Zip::new` for how prepended data is detected */
+        let mut signature = [0; 4];
+        let mut has_central_dir_at = |pos: u64| -> io::Result<bool> {
+            return Ok(central_dir_records_total == 0
+                || (read_at(&mut reader, len, pos, &mut signature)? && signature == raw::CENTRAL_DIR_HEADER_SIGNATURE.to_le_bytes()));
+        };
+        let gap = central_dir_end
+            .checked_sub(central_dir_size)
+            .and_then(|x| x.checked_sub(central_dir_offset));
+        let prefix_len = match gap {
+            Some(gap) if gap != 0 && !has_central_dir_at(central_dir_offset)? && has_central_dir_at(gap + central_dir_offset)? => gap,
+            _ => 0,
+        };
+
+        let central_dir_start = prefix_len + central_dir_offset;
+        let truncated = central_dir_start.checked_add(central_dir_size).is_none_or(|end| end > len);
+        if truncated && central_dir_records_total != 0 {
+            let end = central_dir_offset.saturating_add(central_dir_size);
+            return Err(invalid(ZipError::TruncatedDirectory { end, len: (len - prefix_len) as usize }));
+        }
+        let size = usize::try_from(central_dir_size)
+            .map_err(|_| invalid(ZipError::SizeOverflow { value: central_dir_size }))?;
+        let mut central_dir = std::vec![0; if truncated { 0 } else { size }];
+        reader.seek(SeekFrom::Start(central_dir_start))?;
+        reader.read_exact(&mut central_dir)?;
+
+        return Ok(Self {
+            reader,
+            prefix_len,
+            central_dir_offset: usize::try_from(central_dir_offset).unwrap_or(usize::MAX),
+            central_dir,
+            central_dir_records_total,
+            name_encoding: NameEncoding::Auto,
+        });
+    }
+
+    /// Iterator over the files, same as `Zip::files`, but see the note on `ZipReader`
+    pub fn files(&self) -> impl Iterator<Item=File<'_>> + '_ {
+        let iter = NonStrictIter {
+            data: &self.central_dir,
+            offset: 0,
+            name_encoding: self.name_encoding,
+            remaining: self.central_dir_records_total,
+        };
+        /* Offsets have to point into the whole archive, not the directory */
+        return iter.map(|mut file| {
+            file.data = &[];
+            file.central_record_offset = file.central_record_offset
+                .map(|offset| offset.saturating_add(self.central_dir_offset));
+            return file;
+        });
+    }
+
+    pub fn set_name_encoding(&mut self, name_encoding: NameEncoding) {
+        self.name_encoding = name_encoding;
+    }
+
+    /// Length of the data before the archive, see `Zip::prefix_len`
+    pub fn prefix_len(&self) -> u64 {
+        return self.prefix_len;
+    }
+
+    /// Reader over the compressed data of the `index`-th file in the central
+    /// directory, counted like in `Zip::get`, so records `files` skips still count.
+    /// For stored files that's the content, others have to be decompressed.
+    ///
+    /// Takes an index, because files borrow the directory from `self`.
+    /// Finding it walks the directory, so it's O(n) like `Zip::get`.
+    pub fn entry_reader(&mut self, index: usize) -> io::Result<io::Take<&mut R>> {
+        let (local_file_offset, compressed_size) = Zip::from_parts(&self.central_dir, 0, self.central_dir_records_total)
+            .get(index)
+            .map(|file| (file.local_file_offset as u64, file.compressed_size_u64()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no such file"))?;
+
+        let mut buf = [0; mem::size_of::<raw::LocalFileHeader>()];
+        self.reader.seek(SeekFrom::Start(self.prefix_len.saturating_add(local_file_offset)))?;
+        self.reader.read_exact(&mut buf)?;
+
+        let header = raw::from_prefix::<raw::LocalFileHeader>(&buf)
+            .filter(|header| header.signature.get() == raw::LOCAL_FILE_HEADER_SIGNATURE)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid local file header"))?;
+        let skip = header.filename_len.get() as i64 + header.extra_field_len.get() as i64;
+        self.reader.seek(SeekFrom::Current(skip))?;
+
+        return Ok((&mut self.reader).take(compressed_size));
+    }
+
+    pub fn into_inner(self) -> R {
+        return self.reader;
+    }
+}
+
+fn invalid(err: ZipError) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, err);
+}
+
+/// Fills `buf` with bytes at `pos`, or returns `false` if they're past `len`
+fn read_at<R: Read + Seek>(reader: &mut R, len: u64, pos: u64, buf: &mut [u8]) -> io::Result<bool> {
+    if pos.checked_add(buf.len() as u64).is_none_or(|end| end > len) {
+        return Ok(false);
+    }
+    reader.seek(SeekFrom::Start(pos))?;
+    reader.read_exact(buf)?;
+    return Ok(true);
+}
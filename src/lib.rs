@@ -1,41 +1,822 @@
 #![no_std]
 
-#![feature(array_windows)]
+#![allow(clippy::needless_return)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use core::mem;
+use core::ops::Range;
 
 pub mod raw;
 pub mod extra;
+pub mod descriptor;
+pub mod cp437;
+pub mod crc;
+pub mod time;
+pub mod path;
+pub mod local;
+#[cfg(feature = "alloc")]
+pub mod diff;
+#[cfg(feature = "alloc")]
+pub mod index;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "decompress")]
+pub mod decompress;
 
 use crate::extra::Extra;
+use crate::descriptor::DataDescriptor;
 
 pub struct Zip<'data> {
     pub central_dir_iter: NonStrictIter<'data>,
     pub central_dir_records_total: u64,
+
+    /// Archive itself, without data prepended to it
+    data: &'data [u8],
+    prefix_len: usize,
+    central_dir_offset: usize,
+    central_dir_size: usize,
+    /// Whether the end record defers to the Zip64 one,
+    /// or some entry has a Zip64 extra field
+    zip64: bool,
+    name_encoding: NameEncoding,
+    /// Position of the archive comment in `data`
+    comment: Range<usize>,
+}
+
+/// Knobs for how tolerant `Zip::with_options` is.
+/// Default is what `Zip::new` does.
+#[derive(Clone, Copy, Debug)]
+pub struct OpenOptions {
+    /// Reject the archive if any central directory record has a wrong signature
+    pub require_valid_signatures: bool,
+    /// Reject the archive if any record points to a local file header that
+    /// doesn't fit or has a wrong signature, see `Zip::validate_links`
+    pub require_valid_local_headers: bool,
+    /// Reject archives declaring more entries than this
+    pub max_entries: Option<u64>,
+    /// Accept data prepended to the archive, like a self-extractor stub.
+    /// Stored offsets are relative to the start of the archive, not of the buffer.
+    /// On by default.
+    pub allow_prefix: bool,
+    pub name_encoding: NameEncoding,
+    /// Look for the end of central directory record in the whole buffer,
+    /// see `raw::CentralDirectoryRecordEnd::find_within`
+    pub full_scan: bool,
+    /// Look for the end of central directory record only this many bytes
+    /// before the end of the end record, which caps how long an archive comment
+    /// can be. `None` is the longest possible comment, or the whole buffer
+    /// with `full_scan`.
+    pub max_comment_scan: Option<usize>,
+    /// Reject archives with oddities that are otherwise worked around
+    pub strict: bool,
+    /// Called for every oddity that parsing tolerates
+    pub on_anomaly: Option<fn(Anomaly)>,
+}
+
+/// Something wrong with the archive that parsing worked around
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Anomaly {
+    /// End record of a single-disk archive has different record counts for
+    /// this disk and in total. `used` is the one consistent with the directory.
+    RecordCountMismatch { on_this_disk: u16, total: u16, used: u64 },
+    /// Archive comment is shorter than the end record says, so it got clamped
+    CommentLengthExceedsData { declared: u16, available: usize },
+}
+
+/// Why an archive couldn't be opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ZipError {
+    /// No end of central directory record
+    EndRecordNotFound,
+    /// Buffer is shorter than the smallest possible archive
+    BufferTooSmall { len: usize },
+    /// End record says the archive is Zip64, but there's no Zip64 end of
+    /// central directory locator right before it
+    Zip64LocatorMissing,
+    /// Zip64 end of central directory locator points to nowhere
+    Zip64EndRecordNotFound,
+    /// Archive declares more entries than `OpenOptions::max_entries`
+    TooManyEntries { declared: u64, max: u64 },
+    /// Central directory record with a wrong signature, with
+    /// `OpenOptions::require_valid_signatures`
+    InvalidSignature { index: u64 },
+    /// Central directory record pointing to an invalid local file header,
+    /// with `OpenOptions::require_valid_local_headers`
+    InvalidLocalHeader { index: u64 },
+    /// Archive comment is shorter than the end record says, with `OpenOptions::strict`
+    CommentLengthExceedsData { declared: u16, available: usize },
+    /// Archive is split across several files (disks), and offsets point
+    /// into the ones we don't have
+    SplitArchive { disk: u32, central_dir_start_disk: u32 },
+    /// Several segments of a split archive glued together, recognized by
+    /// the spanning signature at the start. `disk` is the last one.
+    SpannedArchive { disk: u32 },
+    /// Zip64 offset or size doesn't fit in `usize`, only possible on 32-bit targets
+    SizeOverflow { value: u64 },
+    /// Central directory doesn't fit in the buffer, usually a truncated download
+    TruncatedDirectory { end: u64, len: usize },
+}
+
+impl core::fmt::Display for ZipError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EndRecordNotFound => f.write_str("end of central directory record not found"),
+            Self::BufferTooSmall { len } => write!(f, "{len} bytes is too small for a zip archive"),
+            Self::Zip64LocatorMissing => f.write_str("zip64 end of central directory locator not found"),
+            Self::Zip64EndRecordNotFound => f.write_str("zip64 end of central directory record not found"),
+            Self::TooManyEntries { declared, max } =>
+                write!(f, "archive declares {declared} entries, more than allowed {max}"),
+            Self::InvalidSignature { index } =>
+                write!(f, "central directory record {index} has invalid signature"),
+            Self::InvalidLocalHeader { index } =>
+                write!(f, "central directory record {index} points to invalid local file header"),
+            Self::CommentLengthExceedsData { declared, available } =>
+                write!(f, "archive comment is {declared} bytes long, but only {available} bytes follow"),
+            Self::SplitArchive { disk, central_dir_start_disk } =>
+                write!(f, "split archives are not supported (disk {disk}, central directory starts on disk {central_dir_start_disk})"),
+            Self::SpannedArchive { disk } =>
+                write!(f, "spanned archives are not supported ({} segments)", *disk as u64 + 1),
+            Self::SizeOverflow { value } => write!(f, "{value} doesn't fit in usize"),
+            Self::TruncatedDirectory { end, len } =>
+                write!(f, "central directory ends at {end}, past the end of {len} byte archive"),
+        }
+    }
+}
+
+impl core::error::Error for ZipError {}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self {
+            require_valid_signatures: false,
+            require_valid_local_headers: false,
+            max_entries: None,
+            allow_prefix: true,
+            name_encoding: NameEncoding::Auto,
+            full_scan: false,
+            max_comment_scan: None,
+            strict: false,
+            on_anomaly: None,
+        }
+    }
 }
 
 impl<'data> Zip<'data> {
+    /// Files borrow their names and data straight from `data`, so it has to be
+    /// one contiguous buffer. A memory-mapped file derefs to one just fine.
     pub fn new(data: &'data [u8]) -> Option<Self> {
-        let (header, _comment_len) = raw::CentralDirectoryRecordEnd::find(data)?;
+        return Self::parse(data).ok();
+    }
+
+    /// Like `new`, but tells why the archive was rejected
+    pub fn parse(data: &'data [u8]) -> Result<Self, ZipError> {
+        return Self::parse_with_options(data, OpenOptions::default());
+    }
+
+    /// Like `new`, but with archive-wide policy for decoding filenames,
+    /// see `File::decoded_name`
+    pub fn open_with(data: &'data [u8], name_encoding: NameEncoding) -> Option<Self> {
+        return Self::with_options(data, OpenOptions { name_encoding, ..OpenOptions::default() });
+    }
+
+    /// Like `new`, but looks for the end of central directory record in the whole
+    /// buffer, not only in the last 64 KiB, and tolerates junk after the archive comment.
+    ///
+    /// This is O(n) in the buffer size and can mistake compressed data for
+    /// the record, so use it only as a recovery fallback after `new` failed.
+    pub fn open_full_scan(data: &'data [u8]) -> Option<Self> {
+        return Self::with_options(data, OpenOptions { full_scan: true, ..OpenOptions::default() });
+    }
+
+    /// Salvages files from an archive whose central directory is damaged,
+    /// by walking local headers from the start, see `local::RecoverIter`
+    pub fn recover(data: &'data [u8]) -> local::RecoverIter<'data> {
+        return local::RecoverIter::new(data);
+    }
+
+    pub fn with_options(data: &'data [u8], options: OpenOptions) -> Option<Self> {
+        return Self::parse_with_options(data, options).ok();
+    }
+
+    /// Like `with_options`, but tells why the archive was rejected
+    pub fn parse_with_options(data: &'data [u8], options: OpenOptions) -> Result<Self, ZipError> {
+        if data.len() < mem::size_of::<raw::CentralDirectoryRecordEnd>() {
+            return Err(ZipError::BufferTooSmall { len: data.len() });
+        }
+
+        let scan = options.max_comment_scan
+            .map(|len| len.saturating_add(mem::size_of::<raw::CentralDirectoryRecordEnd>()));
+        let (header, _) = if options.full_scan {
+            raw::CentralDirectoryRecordEnd::find_within(data, scan.unwrap_or(data.len()))
+        } else {
+            /* Header still points into `data`, which is what `from_end_record` needs */
+            let start = scan.map_or(0, |scan| data.len().saturating_sub(scan));
+            raw::CentralDirectoryRecordEnd::find(&data[start..])
+        }.ok_or(ZipError::EndRecordNotFound)?;
+
+        let mut zip = Self::from_end_record(data, header, &options)?;
+        zip.set_name_encoding(options.name_encoding);
+
+        if let Some(max) = options.max_entries {
+            if zip.central_dir_records_total > max {
+                return Err(ZipError::TooManyEntries { declared: zip.central_dir_records_total, max });
+            }
+        }
+
+        if options.require_valid_signatures {
+            let invalid = zip.records()
+                .position(|record| record.header.signature.get() != raw::CENTRAL_DIR_HEADER_SIGNATURE);
+            if let Some(index) = invalid {
+                return Err(ZipError::InvalidSignature { index: index as u64 });
+            }
+        }
+
+        if options.require_valid_local_headers {
+            if let Some((index, _)) = zip.first_broken_link() {
+                return Err(ZipError::InvalidLocalHeader { index: index as u64 });
+            }
+        }
+
+        return Ok(zip);
+    }
+
+    /// Archive comment. Clamped to the end of the buffer if the end record
+    /// claims it's longer (see `Anomaly::CommentLengthExceedsData`)
+    pub fn comment(&self) -> &'data [u8] {
+        return &self.data[self.comment.clone()];
+    }
+
+    fn set_name_encoding(&mut self, name_encoding: NameEncoding) {
+        self.name_encoding = name_encoding;
+        self.central_dir_iter.name_encoding = name_encoding;
+    }
+
+    pub fn name_encoding(&self) -> NameEncoding {
+        return self.name_encoding;
+    }
+
+    /// Length of the data before the archive, see `OpenOptions::allow_prefix`
+    pub fn prefix_len(&self) -> usize {
+        return self.prefix_len;
+    }
+
+    /// Whether the archive has a Zip64 end of central directory record,
+    /// or some entry has a Zip64 extra field. Both are checked when opening.
+    pub fn is_zip64(&self) -> bool {
+        return self.zip64;
+    }
+
+    /// Where the central directory is, relative to the start of the archive
+    /// (so after `prefix_len`)
+    pub fn central_directory_range(&self) -> Range<usize> {
+        return self.central_dir_offset .. self.central_dir_offset + self.central_dir_size;
+    }
+
+    fn from_end_record(data: &'data [u8], header: &raw::CentralDirectoryRecordEnd, options: &OpenOptions) -> Result<Self, ZipError> {
+        let end_record_offset = header as *const _ as usize - data.as_ptr() as usize;
+        let allow_prefix = options.allow_prefix;
 
-        // TODO: zip64
-        let central_dir_records_total = header.central_dir_records_total as u64;
+        let mut central_dir_records_total = header.central_dir_records_total.get() as u64;
+        let mut central_dir_offset = header.central_dir_offset.get() as usize;
+        let mut central_dir_size = header.central_dir_size.get() as usize;
+        /* Where the central directory should end */
+        let mut central_dir_end = end_record_offset;
+        let mut disk = header.disk_num.get() as u32;
+        let mut central_dir_start_disk = header.central_dir_start_disk.get() as u32;
+
+        let zip64 = header.needs_zip64();
+        if zip64 {
+            let locator = raw::Zip64CentralDirectoryLocator::find(data, end_record_offset)
+                .ok_or(ZipError::Zip64LocatorMissing)?;
+            let locator_offset = end_record_offset - mem::size_of::<raw::Zip64CentralDirectoryLocator>();
+
+            /* With a prefix the declared offset is off, but the record
+             * is usually right before the locator */
+            let declared = usize::try_from(locator.central_dir_end_offset.get()).ok();
+            let record_offset = declared
+                .filter(|&offset| raw::Zip64CentralDirectoryRecordEnd::at(data, offset).is_some())
+                .or_else(|| {
+                    let offset = locator_offset.checked_sub(mem::size_of::<raw::Zip64CentralDirectoryRecordEnd>())?;
+                    return allow_prefix.then_some(offset);
+                })
+                .ok_or(ZipError::Zip64EndRecordNotFound)?;
+            let record = raw::Zip64CentralDirectoryRecordEnd::at(data, record_offset)
+                .ok_or(ZipError::Zip64EndRecordNotFound)?;
+
+            if header.central_dir_records_total.get() == u16::MAX {
+                central_dir_records_total = record.central_dir_records_total.get();
+            }
+            if header.central_dir_offset.get() == u32::MAX {
+                let value = record.central_dir_offset.get();
+                central_dir_offset = usize::try_from(value).map_err(|_| ZipError::SizeOverflow { value })?;
+            }
+            if header.central_dir_size.get() == u32::MAX {
+                let value = record.central_directory_size.get();
+                central_dir_size = usize::try_from(value).map_err(|_| ZipError::SizeOverflow { value })?;
+            }
+            if header.disk_num.get() == u16::MAX {
+                disk = record.disk_num.get();
+            }
+            if header.central_dir_start_disk.get() == u16::MAX {
+                central_dir_start_disk = record.central_dir_start_disk.get();
+            }
+            central_dir_end = record_offset;
+        }
+
+        let spanning = data.get(..4).is_some_and(|marker| {
+            let marker = u32::from_le_bytes([marker[0], marker[1], marker[2], marker[3]]);
+            return marker == raw::SPANNING_SIGNATURE || marker == raw::TEMPORARY_SPANNING_SIGNATURE;
+        });
+        if spanning && disk != 0 {
+            return Err(ZipError::SpannedArchive { disk });
+        }
+        if disk != 0 || central_dir_start_disk != 0 {
+            return Err(ZipError::SplitArchive { disk, central_dir_start_disk });
+        }
+
+        let comment_offset = end_record_offset + mem::size_of::<raw::CentralDirectoryRecordEnd>();
+        let available = data.len() - comment_offset;
+        let declared = header.comment_length.get();
+        if declared as usize > available {
+            if options.strict {
+                return Err(ZipError::CommentLengthExceedsData { declared, available });
+            }
+            if let Some(on_anomaly) = options.on_anomaly {
+                on_anomaly(Anomaly::CommentLengthExceedsData { declared, available });
+            }
+        }
+        let comment_len = available.min(declared as usize);
+
+        /* The central directory should end right where the end record starts,
+         * so if there's a gap, it's the length of whatever was prepended.
+         * Unless it's junk between the directory and the end record,
+         * so the directory has to actually be there after shifting. */
+        let has_central_dir_at = |offset: usize| {
+            let signature = data.get(offset..).and_then(|bytes| bytes.get(..4));
+            return central_dir_records_total == 0
+                || signature == Some(&raw::CENTRAL_DIR_HEADER_SIGNATURE.to_le_bytes()[..]);
+        };
+        let prefix_len = if allow_prefix {
+            central_dir_end
+                .checked_sub(central_dir_size)
+                .and_then(|x| x.checked_sub(central_dir_offset))
+                .filter(|&prefix_len| prefix_len == 0 || !has_central_dir_at(central_dir_offset))
+                .filter(|&prefix_len| has_central_dir_at(prefix_len + central_dir_offset))
+                .unwrap_or(0)
+        } else if spanning && !has_central_dir_at(central_dir_offset) && has_central_dir_at(central_dir_offset.saturating_add(4)) {
+            /* Single-disk spanned archive with offsets not counting the marker */
+            4
+        } else {
+            0
+        };
+
+        /* Empty archives get a pass, there's nothing to read anyway */
+        let archive_len = data.len() - prefix_len;
+        let central_dir_in_bounds = central_dir_offset
+            .checked_add(central_dir_size)
+            .filter(|&end| end <= archive_len);
+        if central_dir_in_bounds.is_none() && central_dir_records_total != 0 {
+            let end = (central_dir_offset as u64).saturating_add(central_dir_size as u64);
+            return Err(ZipError::TruncatedDirectory { end, len: archive_len });
+        }
+
+        let mut zip = Self::from_parts(&data[prefix_len..], central_dir_offset, central_dir_records_total);
+        zip.prefix_len = prefix_len;
+        zip.central_dir_size = central_dir_size;
+        zip.zip64 = zip64;
+        zip.comment = comment_offset - prefix_len .. comment_offset - prefix_len + comment_len;
+
+        let on_this_disk = header.central_dir_records_on_this_disk.get();
+        let total = header.central_dir_records_total.get();
+        if !zip64 && on_this_disk != total {
+            zip.reconcile_record_counts(on_this_disk, total, options);
+        }
+        /* After reconciling, so that only the records in use count */
+        zip.zip64 = zip64 || zip.records().any(|record| record.has_zip64_extra);
+
+        return Ok(zip);
+    }
+
+    /// Some producers get one of the record counts wrong (usually zeroed) in
+    /// single-disk archives. Use whichever agrees with how many records with
+    /// a valid signature are actually there, preferring the total.
+    fn reconcile_record_counts(&mut self, on_this_disk: u16, total: u16, options: &OpenOptions) {
+        let limit = on_this_disk.max(total) as u64;
+        let walked = self.records_up_to(limit)
+            .take_while(|record| record.header.signature.get() == raw::CENTRAL_DIR_HEADER_SIGNATURE)
+            .count() as u64;
+
+        let used = [total, on_this_disk]
+            .into_iter()
+            .map(u64::from)
+            .find(|&count| count != 0 && count == walked)
+            .unwrap_or(total as u64);
+
+        self.central_dir_records_total = used;
+        self.central_dir_iter.remaining = used;
+        if let Some(on_anomaly) = options.on_anomaly {
+            on_anomaly(Anomaly::RecordCountMismatch { on_this_disk, total, used });
+        }
+    }
+
+    fn from_parts(data: &'data [u8], central_dir_offset: usize, central_dir_records_total: u64) -> Self {
         let central_dir_iter = NonStrictIter {
             data,
-            offset: header.central_dir_offset as usize,
+            offset: central_dir_offset,
+            name_encoding: NameEncoding::Auto,
+            remaining: central_dir_records_total,
         };
 
-        Some(Self {
+        Self {
             central_dir_records_total,
             central_dir_iter,
-        })
+            data,
+            prefix_len: 0,
+            central_dir_offset,
+            central_dir_size: 0,
+            zip64: false,
+            name_encoding: NameEncoding::Auto,
+            comment: 0..0,
+        }
+    }
+
+    /// Checks that every central directory record points at a local file header,
+    /// that is, the header fits in the buffer and has a valid signature.
+    ///
+    /// `NonStrictIter` just stops on a broken link, so this is meant as a pre-flight
+    /// check for rejecting broken archives before extracting anything.
+    /// Returns the first broken link found.
+    pub fn validate_links(&self) -> Result<(), LinkError<'data>> {
+        if let Some((_, record)) = self.first_broken_link() {
+            return Err(LinkError {
+                filename: record.filename,
+                local_file_offset: record.local_file_offset,
+            });
+        }
+
+        return Ok(());
+    }
+
+    /// First record, with its index, whose local file header doesn't fit
+    /// or has a wrong signature
+    fn first_broken_link(&self) -> Option<(usize, CentralRecord<'data>)> {
+        return self.records().enumerate().find(|(_, record)| {
+            return local_file_header(self.data, record.local_file_offset)
+                .is_none_or(|header| header.signature.get() != raw::LOCAL_FILE_HEADER_SIGNATURE);
+        });
+    }
+
+    /// Checks the whole archive: every central directory record, its local file
+    /// header or data descriptor (see `File::validate_local_header`) and that its
+    /// data fits, and that the record count matches the end record.
+    /// Unlike `validate_links` and `strict_iter`, this reports all problems found,
+    /// though it can't get past a record that doesn't fit in the archive.
+    ///
+    /// Content isn't checked, see `validate_decompressed` for that.
+    #[cfg(feature = "alloc")]
+    pub fn validate(&self) -> Result<(), alloc::vec::Vec<ValidationError<'data>>> {
+        let mut errors = alloc::vec::Vec::new();
+        let mut found = 0;
+        let mut end = self.central_dir_offset;
+
+        for record in self.records() {
+            let offset = record.header as *const _ as usize - self.data.as_ptr() as usize;
+            found += 1;
+            end = offset + record.len;
+
+            if record.header.signature.get() != raw::CENTRAL_DIR_HEADER_SIGNATURE {
+                errors.push(ValidationError::Directory(StrictError::InvalidSignature { offset }));
+            }
+            if record.filename.is_empty() {
+                errors.push(ValidationError::Directory(StrictError::EmptyFilename { offset }));
+            }
+
+            let method = record.header.compression_method.get();
+            let size = record.decompressed_size;
+            let Some(file) = record.into_file(self.data, self.name_encoding) else {
+                let error = if usize::try_from(size).is_err() {
+                    StrictError::SizeOverflow { offset, size }
+                } else {
+                    StrictError::UnknownCompressionMethod { offset, method }
+                };
+                errors.push(ValidationError::Directory(error));
+                continue;
+            };
+
+            if let Err(error) = file.validate_local_header() {
+                errors.push(ValidationError::LocalHeader { filename: file.filename, error });
+            } else if file.data_range().is_none() {
+                errors.push(ValidationError::DataOutOfBounds { filename: file.filename });
+            }
+        }
+
+        /* Leftover records or the directory ending early are reported the same way
+         * `StrictIter` does */
+        let mut rest = StrictIter {
+            data: self.data,
+            offset: end,
+            declared: self.central_dir_records_total,
+            remaining: self.central_dir_records_total - found,
+            name_encoding: self.name_encoding,
+            failed: false,
+        };
+        if let Some(Err(error)) = rest.next() {
+            errors.push(ValidationError::Directory(error));
+        }
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+        return Err(errors);
+    }
+
+    /// Counts the central directory records without resolving local file headers,
+    /// which makes it much cheaper than `central_dir_iter.count()`.
+    ///
+    /// Stops at the declared number of records or at the first record that
+    /// doesn't fit in the buffer, whichever comes first.
+    pub fn quick_count(&self) -> u64 {
+        return self.records().count() as u64;
+    }
+
+    /// Number of records the end record declares, same as `central_dir_records_total`.
+    /// O(1), but unlike `quick_count` it doesn't check they're actually there.
+    pub fn len(&self) -> u64 {
+        return self.central_dir_records_total;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.central_dir_records_total == 0;
+    }
+
+    /// The `index`-th file in the central directory, or `None` past the declared
+    /// number of records. This walks the directory from the start, for repeated
+    /// access see `index::IndexedZip`.
+    pub fn get(&self, index: usize) -> Option<File<'data>> {
+        return self.records()
+            .nth(index)?
+            .into_file(self.data, self.name_encoding);
+    }
+
+    /// First file named exactly `name`, found by a linear scan of the central directory
+    pub fn by_name(&self, name: &[u8]) -> Option<File<'data>> {
+        return self.records()
+            .filter(|record| record.filename == name)
+            .find_map(|record| record.into_file(self.data, self.name_encoding));
+    }
+
+    /// First file named `name`, ignoring ASCII case, for archives made
+    /// on case-insensitive filesystems
+    pub fn by_name_ignore_ascii_case(&self, name: &[u8]) -> Option<File<'data>> {
+        return self.records()
+            .filter(|record| record.filename.eq_ignore_ascii_case(name))
+            .find_map(|record| record.into_file(self.data, self.name_encoding));
+    }
+
+    /// Files whose names start with `prefix`, like `assets/` for one subtree.
+    /// Local headers are resolved only for the matching ones.
+    pub fn entries_with_prefix<'p>(&self, prefix: &'p [u8]) -> impl Iterator<Item = File<'data>> + 'p
+    where
+        'data: 'p,
+    {
+        let data = self.data;
+        let name_encoding = self.name_encoding;
+        return self.records()
+            .filter(move |record| record.filename.starts_with(prefix))
+            .filter_map(move |record| record.into_file(data, name_encoding));
+    }
+
+    /// One item per declared record, `None` for records `files` would skip,
+    /// so that `len()` is exact, see `EntryIter`
+    pub fn entries(&self) -> EntryIter<'data> {
+        return EntryIter {
+            data: self.data,
+            offset: self.central_dir_offset,
+            name_encoding: self.name_encoding,
+            remaining: self.central_dir_records_total,
+            broken: false,
+        };
+    }
+
+    /// Files compressed with `method`, skipping the rest,
+    /// including ones with methods `CompressionMethod` doesn't know
+    pub fn entries_with_method(&self, method: CompressionMethod) -> impl Iterator<Item = File<'data>> {
+        let data = self.data;
+        let name_encoding = self.name_encoding;
+        return self.records()
+            .filter(move |record| record.header.compression_method.get() == method as u16)
+            .filter_map(move |record| record.into_file(data, name_encoding));
+    }
+
+    /// New iterator over the files from the start of the central directory,
+    /// unlike `central_dir_iter`, which is used up after one pass
+    pub fn files(&self) -> NonStrictIter<'data> {
+        return NonStrictIter {
+            data: self.data,
+            offset: self.central_dir_offset,
+            name_encoding: self.name_encoding,
+            remaining: self.central_dir_records_total,
+        };
+    }
+
+    /// Iterator which reports malformed records, see `StrictIter`
+    pub fn strict_iter(&self) -> StrictIter<'data> {
+        return StrictIter {
+            data: self.data,
+            offset: self.central_dir_offset,
+            declared: self.central_dir_records_total,
+            remaining: self.central_dir_records_total,
+            name_encoding: self.name_encoding,
+            failed: false,
+        };
+    }
+
+    /// Central directory records, without resolving local headers,
+    /// up to the declared number of records
+    fn records(&self) -> impl Iterator<Item = CentralRecord<'data>> {
+        return self.records_up_to(self.central_dir_records_total);
+    }
+
+    fn records_up_to(&self, limit: u64) -> impl Iterator<Item = CentralRecord<'data>> {
+        let data = self.data;
+        let mut offset = self.central_dir_offset;
+
+        return (0..limit).map_while(move |_| {
+            let record = CentralRecord::parse(data.get(offset..)?)?;
+            offset += record.len;
+            return Some(record);
+        });
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Archive in a mutable buffer, for transforming entries' data in place,
+/// like decrypting them before decompression without making an owned copy.
+///
+/// Reading goes through `as_zip`, which borrows the buffer immutably,
+/// so get the entry's index from there and then use `bytes_mut`.
+/// Indices count central directory records, as in `Zip::get`.
+pub struct ZipMut<'data> {
+    data: &'data mut [u8],
+    prefix_len: usize,
+    central_dir_offset: usize,
+    central_dir_size: usize,
+    zip64: bool,
+    central_dir_records_total: u64,
+    name_encoding: NameEncoding,
+    comment: Range<usize>,
+}
+
+impl<'data> ZipMut<'data> {
+    pub fn new(data: &'data mut [u8]) -> Option<Self> {
+        return Self::with_options(data, OpenOptions::default());
+    }
+
+    pub fn with_options(data: &'data mut [u8], options: OpenOptions) -> Option<Self> {
+        let zip = Zip::with_options(data, options)?;
+        let prefix_len = zip.prefix_len;
+        let central_dir_offset = zip.central_dir_offset;
+        let central_dir_size = zip.central_dir_size;
+        let zip64 = zip.zip64;
+        let central_dir_records_total = zip.central_dir_records_total;
+        let name_encoding = zip.name_encoding;
+        let comment = zip.comment.clone();
+
+        return Some(Self {
+            data,
+            prefix_len,
+            central_dir_offset,
+            central_dir_size,
+            zip64,
+            central_dir_records_total,
+            name_encoding,
+            comment,
+        });
+    }
+
+    pub fn set_name_encoding(&mut self, name_encoding: NameEncoding) {
+        self.name_encoding = name_encoding;
+    }
+
+    pub fn as_zip(&self) -> Zip<'_> {
+        let mut zip = Zip::from_parts(&self.data[self.prefix_len..], self.central_dir_offset, self.central_dir_records_total);
+        zip.prefix_len = self.prefix_len;
+        zip.central_dir_size = self.central_dir_size;
+        zip.zip64 = self.zip64;
+        zip.comment = self.comment.clone();
+        zip.set_name_encoding(self.name_encoding);
+        return zip;
+    }
+
+    /// Iterator over the files, same as `Zip::central_dir_iter`
+    pub fn files(&self) -> NonStrictIter<'_> {
+        return self.as_zip().central_dir_iter;
+    }
+
+    /// Data of the `index`-th file in the central directory, in its stored form.
+    /// Records `files` skips still count, see `Zip::get`.
+    pub fn bytes_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        let range = self.as_zip().get(index)?.data_range()?;
+        return self.data[self.prefix_len..].get_mut(range);
+    }
+}
+
+/// Problem found by `Zip::validate`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError<'data> {
+    /// Central directory record is broken, see `StrictError`
+    Directory(StrictError),
+    /// Local file header is missing or disagrees with the central directory
+    LocalHeader { filename: &'data [u8], error: MismatchError },
+    /// Compressed data doesn't fit in the archive
+    DataOutOfBounds { filename: &'data [u8] },
+    /// Content couldn't be decompressed, or has a wrong size or CRC,
+    /// see `Zip::validate_decompressed`
+    #[cfg(feature = "decompress")]
+    Content { filename: &'data [u8], error: decompress::DecompressError },
+}
+
+impl<'data> ValidationError<'data> {
+    /// Name of the file with the problem, `None` for directory problems
+    pub fn filename(&self) -> Option<&'data [u8]> {
+        return match *self {
+            Self::Directory(_) => None,
+            Self::LocalHeader { filename, .. } | Self::DataOutOfBounds { filename } => Some(filename),
+            #[cfg(feature = "decompress")]
+            Self::Content { filename, .. } => Some(filename),
+        };
+    }
+}
+
+impl<'data> core::fmt::Display for ValidationError<'data> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Directory(error) => error.fmt(f),
+            Self::LocalHeader { filename, error } => write!(f, "{:?}: {error}", filename.escape_ascii()),
+            Self::DataOutOfBounds { filename } => write!(f, "{:?}: compressed data out of bounds", filename.escape_ascii()),
+            #[cfg(feature = "decompress")]
+            Self::Content { filename, error } => write!(f, "{:?}: {error}", filename.escape_ascii()),
+        }
+    }
+}
+
+impl<'data> core::error::Error for ValidationError<'data> {}
+
+/// Central directory record which doesn't point at a valid local file header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LinkError<'data> {
+    pub filename: &'data [u8],
+    pub local_file_offset: usize,
+}
+
+impl<'data> core::fmt::Display for LinkError<'data> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "no local file header at offset {} for {:?}",
+            self.local_file_offset, self.filename.escape_ascii())
+    }
+}
+
+impl<'data> core::error::Error for LinkError<'data> {}
+
+/// Compression method of an entry.
+///
+/// More methods keep getting registered, so matching on it requires a wildcard arm:
+///
+/// ```compile_fail
+/// # use piz::CompressionMethod::*;
+/// fn name(method: piz::CompressionMethod) -> &'static str {
+///     match method {
+///         Plain => "stored",
+///         Shrink | Reduce1 | Reduce2 | Reduce3 | Reduce4 | Implode => "legacy",
+///         Deflate | Deflate64 => "deflate",
+///         IbmTerseOld | Bzip2 | Lzma | IbmCmpsc | IbmTerseNew | IbmLz77 => "other",
+///         Zstd | Mp3 | Xz | Jpeg | WavPack | Ppmd1 => "modern",
+///         Aes => "encrypted",
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum CompressionMethod {
     Plain = 0,
 
+    Shrink = 1,
+    /// Reduce with compression factor 1
+    Reduce1 = 2,
+    /// Reduce with compression factor 2
+    Reduce2 = 3,
+    /// Reduce with compression factor 3
+    Reduce3 = 4,
+    /// Reduce with compression factor 4
+    Reduce4 = 5,
+    Implode = 6,
+
     Deflate = 8,
     Deflate64 = 9,
     IbmTerseOld = 10,
@@ -51,23 +832,149 @@ pub enum CompressionMethod {
     Jpeg = 96,
     WavPack = 97,
     Ppmd1 = 98,
+    /// WinZip AES encryption, actual method is in `extra::AesExtra`
+    Aes = 99,
 }
 
 impl CompressionMethod {
     pub const fn from_u16(x: u16) -> Option<Self> {
         let ret = match x {
             0 => Self::Plain,
+            1 => Self::Shrink,
+            2 => Self::Reduce1,
+            3 => Self::Reduce2,
+            4 => Self::Reduce3,
+            5 => Self::Reduce4,
+            6 => Self::Implode,
             8 => Self::Deflate,
+            9 => Self::Deflate64,
+            10 => Self::IbmTerseOld,
+            12 => Self::Bzip2,
+            14 => Self::Lzma,
+            16 => Self::IbmCmpsc,
+            18 => Self::IbmTerseNew,
+            19 => Self::IbmLz77,
+            93 => Self::Zstd,
+            94 => Self::Mp3,
+            95 => Self::Xz,
+            96 => Self::Jpeg,
+            97 => Self::WavPack,
+            98 => Self::Ppmd1,
+            99 => Self::Aes,
             _ => return None,
         };
         return Some(ret);
     }
 }
 
-#[derive(Debug)]
-pub struct ExtraFields;
+/// Extra fields block of a header
+#[derive(Clone, Copy, Debug)]
+pub struct ExtraFields<'data>(pub &'data [u8]);
+
+impl<'data> ExtraFields<'data> {
+    pub fn iter(&self) -> extra::Iter<'data> {
+        return extra::Iter { data: self.0 };
+    }
+
+    /// Data of the first field with given signature
+    pub fn get(&self, signature: u16) -> Option<&'data [u8]> {
+        let (_, field) = self.iter().find(|&(s, _)| s == signature)?;
+        return Some(field);
+    }
+
+    /// First field of type `E`, if there's one and it parses
+    pub fn parse<E: Extra<'data>>(&self) -> Option<E> {
+        return extra::find(self.0);
+    }
+}
+
+impl<'data> IntoIterator for ExtraFields<'data> {
+    type Item = (u16, &'data [u8]);
+    type IntoIter = extra::Iter<'data>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        return self.iter();
+    }
+}
+
+/// How to decode filenames
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NameEncoding {
+    /// UTF-8 if general purpose flag bit 11 is set, CP437 otherwise, as the spec says
+    #[default]
+    Auto,
+    /// Always UTF-8, for archives from tools that don't set the flag
+    Utf8,
+    /// Always CP437
+    Cp437,
+}
+
+/// General purpose bit flag of a local or central directory header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeneralPurposeFlags(pub u16);
+
+impl GeneralPurposeFlags {
+    /// Bit 0 - file is encrypted
+    pub const ENCRYPTED: u16 = 1 << 0;
+    /// Bits 1 and 2 - compression option, meaning depends on the method
+    pub const COMPRESSION_OPTION: u16 = 0b11 << 1;
+    /// Bit 1 for LZMA - the stream ends with an end-of-stream marker
+    pub const LZMA_END_MARKER: u16 = 1 << 1;
+    /// Bit 3 - sizes and CRC are in a data descriptor after the data
+    pub const DATA_DESCRIPTOR: u16 = 1 << 3;
+    /// Bit 6 - strong encryption, on top of bit 0
+    pub const STRONG_ENCRYPTION: u16 = 1 << 6;
+    /// Bit 11 - filename and comment are UTF-8
+    pub const UTF8: u16 = 1 << 11;
+
+    pub const fn is_encrypted(self) -> bool {
+        return self.0 & Self::ENCRYPTED != 0;
+    }
+
+    pub const fn has_data_descriptor(self) -> bool {
+        return self.0 & Self::DATA_DESCRIPTOR != 0;
+    }
+
+    /// Strong encryption bit, which counts only together with the encryption one
+    pub const fn is_strong_encrypted(self) -> bool {
+        return self.is_encrypted() && self.0 & Self::STRONG_ENCRYPTION != 0;
+    }
+
+    pub const fn is_utf8(self) -> bool {
+        return self.0 & Self::UTF8 != 0;
+    }
+
+    /// Whether an LZMA stream ends with an end-of-stream marker,
+    /// otherwise it ends after the decompressed size
+    pub const fn has_lzma_end_marker(self) -> bool {
+        return self.0 & Self::LZMA_END_MARKER != 0;
+    }
+
+    /// Compression level hint, meaningful only for Deflate and Deflate64
+    pub const fn deflate_level(self) -> DeflateLevel {
+        return match (self.0 & Self::COMPRESSION_OPTION) >> 1 {
+            0 => DeflateLevel::Normal,
+            1 => DeflateLevel::Maximum,
+            2 => DeflateLevel::Fast,
+            _ => DeflateLevel::SuperFast,
+        };
+    }
+}
+
+/// Compression level the Deflate encoder was told to use, from general purpose flag bits 1 and 2
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeflateLevel {
+    Normal,
+    Maximum,
+    Fast,
+    SuperFast,
+}
+
+/// File type bits of Unix mode
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
 
-#[derive(Debug)]
 pub struct File<'data> {
     pub decompressed_crc: u32,
     pub decompressed_size: usize,
@@ -75,7 +982,501 @@ pub struct File<'data> {
     pub extra_fields: &'data [u8],
     pub filename: &'data [u8],
     pub comment: &'data [u8],
-    pub bytes: &'data [u8],
+
+    header: raw::CentralDirectoryFileHeader,
+    compressed_size: u64,
+    decompressed_size_u64: u64,
+    has_zip64_extra: bool,
+    name_encoding: NameEncoding,
+    /// Whole archive, for resolving the local file header on demand
+    data: &'data [u8],
+    local_file_offset: usize,
+    /// `None` for files that don't come from the central directory
+    central_record_offset: Option<usize>,
+}
+
+impl<'data> File<'data> {
+    /// Full size of the decompressed data, which on 32-bit targets might not fit
+    /// in `decompressed_size`. Useful for rejecting entries too big to buffer.
+    pub fn uncompressed_size_u64(&self) -> u64 {
+        return self.decompressed_size_u64;
+    }
+
+    /// Full size of the compressed data, see `uncompressed_size_u64`
+    pub fn compressed_size_u64(&self) -> u64 {
+        return self.compressed_size;
+    }
+
+    /// Compressed size divided by decompressed size, so smaller is better.
+    /// `None` for empty files.
+    pub fn compression_ratio(&self) -> Option<f32> {
+        if self.decompressed_size_u64 == 0 {
+            return None;
+        }
+        return Some(self.compressed_size as f32 / self.decompressed_size_u64 as f32);
+    }
+
+    /// Central directory header as it is in the archive, for the fields
+    /// not exposed otherwise. Files from `local::LocalHeaderIter` get one
+    /// made up from the local header.
+    pub fn raw_header(&self) -> &raw::CentralDirectoryFileHeader {
+        return &self.header;
+    }
+
+    /// First extra field of type `E` in the central directory record,
+    /// e.g. `file.extra::<extra::ExtendedTimestamp>()`
+    pub fn extra<E: Extra<'data>>(&self) -> Option<E> {
+        return extra::find(self.extra_fields);
+    }
+
+    /// `(signature, data)` pairs of the central directory extra fields
+    pub fn extra_fields_iter(&self) -> extra::Iter<'data> {
+        return extra::Iter { data: self.extra_fields };
+    }
+
+    /// Where the local file header is, relative to the start of the archive
+    pub fn local_file_offset(&self) -> usize {
+        return self.local_file_offset;
+    }
+
+    /// Where the central directory record of this file is, relative to the
+    /// start of the archive. `None` for files from `local::LocalHeaderIter`.
+    pub fn central_record_offset(&self) -> Option<usize> {
+        return self.central_record_offset;
+    }
+
+    /// Copy of the local file header, `None` if it doesn't fit in the archive.
+    /// Signature isn't checked, see `validate_local_header`.
+    pub fn local_header(&self) -> Option<raw::LocalFileHeader> {
+        return local_file_header(self.data, self.local_file_offset).copied();
+    }
+
+    /// Extra fields of the local file header, which may differ from the central
+    /// directory ones. `None` if the local header doesn't fit in the archive.
+    pub fn local_extra_fields(&self) -> Option<&'data [u8]> {
+        let local_file_header = local_file_header(self.data, self.local_file_offset)?;
+        let start = self.local_file_offset
+            .checked_add(mem::size_of::<raw::LocalFileHeader>())?
+            .checked_add(local_file_header.filename_len.get() as usize)?;
+        let end = start.checked_add(local_file_header.extra_field_len.get() as usize)?;
+        return self.data.get(start..end);
+    }
+
+    /// Extra fields of the central directory record that `extra` module doesn't parse
+    pub fn unknown_extras(&self) -> impl Iterator<Item = (u16, &'data [u8])> {
+        return extra::unknown(self.extra_fields);
+    }
+
+    /// Whether sizes of this entry are stored as 64-bit values.
+    /// There is no flag for this, so we guess it from the minimal version
+    /// needed to extract (4.5 introduced Zip64) or the presence of Zip64 extra field.
+    pub fn is_zip64(&self) -> bool {
+        return self.has_zip64_extra || self.version_needed() >= 45;
+    }
+
+    /// Minimal version of the spec needed to extract this entry,
+    /// as major * 10 + minor, from the central directory
+    pub fn version_needed(&self) -> u16 {
+        return self.header.version_min.get();
+    }
+
+    /// Filename from the Unicode path extra field if there's one made for
+    /// this filename, or the filename itself otherwise
+    pub fn preferred_filename(&self) -> &'data [u8] {
+        return match self.extra::<extra::UnicodePath>() {
+            Some(path) if path.version == 1 && path.matches(self.filename) => path.name,
+            _ => self.filename,
+        };
+    }
+
+    /// Comment from the Unicode comment extra field if there's one made for
+    /// this comment, or the comment itself otherwise
+    pub fn preferred_comment(&self) -> &'data [u8] {
+        return match self.extra::<extra::UnicodeComment>() {
+            Some(comment) if comment.version == 1 && comment.matches(self.comment) => comment.comment,
+            _ => self.comment,
+        };
+    }
+
+    /// Whether the archiver thought this is a text file (bit 0 of internal
+    /// attributes). Only a hint, plenty of archivers never set it.
+    pub fn is_text(&self) -> bool {
+        return self.header.file_attr_internal.get() & 1 != 0;
+    }
+
+    /// Unix `st_mode` (file type and permissions) from the high half of
+    /// external attributes, if the archive was made on Unix.
+    /// Other systems store something else there, mostly DOS attributes.
+    pub fn unix_mode(&self) -> Option<u32> {
+        const HOST_UNIX: u16 = 3;
+        if self.header.version_made_by.get() >> 8 != HOST_UNIX {
+            return None;
+        }
+        return Some(self.header.file_attr_external.get() >> 16);
+    }
+
+    /// Whether this is a directory entry - its name ends with `/`
+    /// or its Unix mode says so
+    pub fn is_dir(&self) -> bool {
+        return self.filename.ends_with(b"/")
+            || self.unix_mode().is_some_and(|mode| mode & S_IFMT == S_IFDIR);
+    }
+
+    /// Whether this is a symbolic link, according to its Unix mode.
+    /// Data of a symlink is the path it points to.
+    pub fn is_symlink(&self) -> bool {
+        return self.unix_mode().is_some_and(|mode| mode & S_IFMT == S_IFLNK);
+    }
+
+    /// General purpose bit flag from the central directory
+    pub fn flags(&self) -> GeneralPurposeFlags {
+        return GeneralPurposeFlags(self.header.flags.get());
+    }
+
+    /// Whether data of this file is encrypted, so `bytes` is ciphertext
+    pub fn is_encrypted(&self) -> bool {
+        return self.flags().is_encrypted();
+    }
+
+    /// Whether this file is encrypted with WinZip AES, see `extra::AesExtra`
+    /// for the strength and the actual compression method
+    pub fn is_aes_encrypted(&self) -> bool {
+        return self.compression_method == CompressionMethod::Aes;
+    }
+
+    /// Whether this file uses PKWARE strong encryption
+    /// rather than the traditional one
+    pub fn is_strong_encrypted(&self) -> bool {
+        return self.flags().is_strong_encrypted();
+    }
+
+    /// Filename if it's safe to use as a relative path for extraction,
+    /// see `path::sanitize`
+    pub fn safe_path(&self) -> Option<&'data str> {
+        return path::sanitize(self.filename);
+    }
+
+    /// Filename split on `/`, skipping empty components,
+    /// so `a//b/` yields `a` and `b`
+    pub fn path_components(&self) -> impl Iterator<Item = &'data [u8]> {
+        return self.filename.split(|&b| b == b'/').filter(|component| !component.is_empty());
+    }
+
+    /// Whether general purpose flag bit 11 says filename and comment are UTF-8.
+    /// Unlike `name_is_utf8`, this ignores the archive's `NameEncoding`.
+    pub fn filename_is_utf8(&self) -> bool {
+        return self.flags().is_utf8();
+    }
+
+    /// Filename as `str`, if it should be decoded as UTF-8 (see `name_is_utf8`)
+    /// and it is valid UTF-8. Use `decoded_name` for CP437 names.
+    pub fn filename_str(&self) -> Option<&'data str> {
+        if !self.name_is_utf8() {
+            return None;
+        }
+        return core::str::from_utf8(self.filename).ok();
+    }
+
+    /// Whether filename should be decoded as UTF-8 (as opposed to CP437)
+    /// according to the archive's `NameEncoding`
+    pub fn name_is_utf8(&self) -> bool {
+        return match self.name_encoding {
+            NameEncoding::Auto => self.filename_is_utf8(),
+            NameEncoding::Utf8 => true,
+            NameEncoding::Cp437 => false,
+        };
+    }
+
+    /// Filename decoded according to the archive's `NameEncoding`.
+    /// Invalid UTF-8 is replaced with U+FFFD.
+    #[cfg(feature = "alloc")]
+    pub fn decoded_name(&self) -> alloc::borrow::Cow<'data, str> {
+        if self.name_is_utf8() {
+            return alloc::string::String::from_utf8_lossy(self.filename);
+        }
+        return cp437::decode(self.filename);
+    }
+
+    /// Filename for display, never fails. Like `decoded_name`, except that
+    /// a name that should be UTF-8 but isn't is decoded as CP437 instead of
+    /// getting U+FFFD, since that's the usual reason for it not being UTF-8.
+    #[cfg(feature = "alloc")]
+    pub fn filename_lossy(&self) -> alloc::borrow::Cow<'data, str> {
+        if self.name_is_utf8() {
+            if let Ok(name) = core::str::from_utf8(self.filename) {
+                return alloc::borrow::Cow::Borrowed(name);
+            }
+        }
+        return cp437::decode(self.filename);
+    }
+
+    /// Last modification time from the central directory header,
+    /// or `None` if it's not set
+    pub fn modified(&self) -> Option<time::DosDateTime> {
+        return time::DosDateTime::from_dos(self.header.last_mod_date.get(), self.header.last_mod_time.get());
+    }
+
+    /// Last modification time, from the extended timestamp extra field if
+    /// there's one, or from the DOS date and time otherwise. DOS time has no
+    /// time zone, so it's taken as UTC, even though it's usually local time.
+    #[cfg(feature = "time")]
+    pub fn modified_datetime(&self) -> Option<::time::OffsetDateTime> {
+        let unix = self.extra::<extra::ExtendedTimestamp>().and_then(|timestamp| timestamp.modified);
+        if let Some(unix) = unix {
+            return ::time::OffsetDateTime::from_unix_timestamp(unix as i64).ok();
+        }
+        return Some(self.modified()?.to_primitive()?.assume_utc());
+    }
+
+    /// Checks decompressed `data` against `decompressed_crc`
+    pub fn verify_crc(&self, data: &[u8]) -> bool {
+        return Self::crc_of(data) == self.decompressed_crc;
+    }
+
+    /// CRC-32 of `data` as ZIP computes it, see `crc::crc32`
+    pub fn crc_of(data: &[u8]) -> u32 {
+        return crc::crc32(data);
+    }
+
+    /// Checks decompressed `data` against `decompressed_crc` computed with
+    /// the given polynomial, for archives known to use a non-standard one
+    pub fn verify_crc_with(&self, data: &[u8], poly: crc::CrcPoly) -> bool {
+        return poly.checksum(data) == self.decompressed_crc;
+    }
+
+    /// Position of the compressed data in the archive buffer.
+    /// This resolves the local file header, so it fails if the header or the data
+    /// doesn't fit in the buffer.
+    pub fn data_range(&self) -> Option<Range<usize>> {
+        let local_file_header = local_file_header(self.data, self.local_file_offset)?;
+        let packed_file_offset = self.local_file_offset.checked_add(
+            mem::size_of::<raw::LocalFileHeader>() +
+            local_file_header.filename_len.get() as usize +
+            local_file_header.extra_field_len.get() as usize
+        )?;
+        let compressed_size = usize::try_from(self.compressed_size).ok()?;
+        let range = packed_file_offset .. packed_file_offset.checked_add(compressed_size)?;
+
+        /* Empty range right at the end of the buffer is still in bounds,
+         * so empty files get an empty slice there too */
+        self.data.get(range.clone())?;
+        return Some(range);
+    }
+
+    /// Compressed data of this file.
+    /// Not read during iteration, so listing an archive doesn't touch the local headers.
+    pub fn bytes(&self) -> Option<&'data [u8]> {
+        return self.data.get(self.data_range()?);
+    }
+
+    /// Opens a stored (`Plain`) entry as a nested archive.
+    /// Returns `None` for compressed entries, which have to be decompressed first.
+    pub fn as_zip(&self) -> Option<Zip<'data>> {
+        if !matches!(self.compression_method, CompressionMethod::Plain) {
+            return None;
+        }
+
+        let mut zip = Zip::new(self.bytes()?)?;
+        zip.set_name_encoding(self.name_encoding);
+        return Some(zip);
+    }
+
+    /// Whether general purpose flag bit 3 is set, meaning the file was written
+    /// in a streaming way: CRC and sizes in the local header are unreliable
+    /// (usually zeroed) and the real ones follow the data in a data descriptor.
+    /// Sizes here are always taken from the central directory, so this only
+    /// matters for reading the local header directly.
+    pub fn has_data_descriptor(&self) -> bool {
+        return self.flags().has_data_descriptor();
+    }
+
+    /// Parses the data descriptor following the compressed data.
+    /// Returns `None` if general purpose flag bit 3 is not set or the descriptor
+    /// doesn't fit in the archive.
+    pub fn data_descriptor(&self) -> Option<DataDescriptor> {
+        if !self.has_data_descriptor() {
+            return None;
+        }
+
+        let offset = self.data_range()?.end;
+        return DataDescriptor::parse(self.data.get(offset..)?, self.is_zip64());
+    }
+
+    /// Length of the data descriptor following the compressed data, 0 if there
+    /// is none. That's where the next local header starts in a streamed archive.
+    ///
+    /// It is 12 bytes, 4 more if the optional signature is present
+    /// (which we peek at) and 8 more if the entry is Zip64.
+    pub fn data_descriptor_size(&self) -> usize {
+        if !self.has_data_descriptor() {
+            return 0;
+        }
+
+        let has_signature = self.data_range()
+            .and_then(|range| self.data.get(range.end..)?.get(..4))
+            .is_some_and(|signature| signature == raw::DATA_DESCRIPTOR_SIGNATURE.to_le_bytes());
+        let signature_len = if has_signature { 4 } else { 0 };
+        let sizes_len = if self.is_zip64() { 16 } else { 8 };
+
+        return signature_len + 4 + sizes_len;
+    }
+
+    /// Checks that the data descriptor, if general purpose flag bit 3 says there
+    /// is one, agrees with the central directory on the CRC.
+    ///
+    /// Different CRCs for the same entry mean a broken producer or tampering.
+    pub fn check_data_descriptor(&self) -> Result<(), MismatchError> {
+        if !self.has_data_descriptor() {
+            return Ok(());
+        }
+
+        let descriptor = self.data_descriptor().ok_or(MismatchError::DataDescriptorMissing)?;
+        if descriptor.crc != self.decompressed_crc {
+            return Err(MismatchError::CrcFieldMismatch {
+                central: self.decompressed_crc,
+                other: descriptor.crc,
+            });
+        }
+
+        return Ok(());
+    }
+
+    /// Checks the local file header: its signature, and that it agrees with
+    /// the central directory on compression method, filename, and CRC and
+    /// sizes. When they're deferred to a data descriptor, the descriptor is
+    /// checked instead, see `check_data_descriptor`.
+    ///
+    /// Iteration doesn't check any of this, so it stays fast.
+    pub fn validate_local_header(&self) -> Result<(), MismatchError> {
+        let local = local_file_header(self.data, self.local_file_offset)
+            .ok_or(MismatchError::LocalHeaderMissing)?;
+        if local.signature.get() != raw::LOCAL_FILE_HEADER_SIGNATURE {
+            return Err(MismatchError::InvalidLocalSignature { found: local.signature.get() });
+        }
+
+        let central = self.header.compression_method.get();
+        if local.compression_method.get() != central {
+            return Err(MismatchError::CompressionMethodMismatch { central, local: local.compression_method.get() });
+        }
+
+        let name_offset = self.local_file_offset + mem::size_of::<raw::LocalFileHeader>();
+        let name = self.data.get(name_offset..)
+            .and_then(|name| name.get(..local.filename_len.get() as usize))
+            .ok_or(MismatchError::LocalHeaderMissing)?;
+        if name != self.filename {
+            return Err(MismatchError::FilenameMismatch);
+        }
+
+        /* Streaming producers write zeros here, real values are in the descriptor */
+        if self.has_data_descriptor() {
+            return self.check_data_descriptor();
+        }
+
+        if local.uncompressed_crc.get() != self.decompressed_crc {
+            return Err(MismatchError::CrcFieldMismatch {
+                central: self.decompressed_crc,
+                other: local.uncompressed_crc.get(),
+            });
+        }
+
+        /* Zip64 extra field in the local header always has both sizes */
+        let mut local_zip64 = self.local_extra_fields()
+            .and_then(|data| extra::Iter { data }.find(|&(signature, _)| signature == extra::Zip64::SIGNATURE))
+            .map(|(_, field)| field)
+            .unwrap_or_default();
+        let mut local_size = |size: u32| match size {
+            u32::MAX => take_u64(&mut local_zip64),
+            size => Some(size as u64),
+        };
+        let local_decompressed = local_size(local.uncompressed_size.get());
+        let local_compressed = local_size(local.compressed_size.get());
+
+        if local_compressed != Some(self.compressed_size) {
+            return Err(MismatchError::CompressedSizeMismatch { central: self.compressed_size, local: local_compressed });
+        }
+        if local_decompressed != Some(self.decompressed_size_u64) {
+            return Err(MismatchError::DecompressedSizeMismatch { central: self.decompressed_size_u64, local: local_decompressed });
+        }
+
+        return Ok(());
+    }
+}
+
+/// Disagreement between copies of the same metadata stored in different places
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MismatchError {
+    /// Flags say there is a data descriptor, but it doesn't fit in the archive
+    DataDescriptorMissing,
+    CrcFieldMismatch { central: u32, other: u32 },
+    /// Local file header doesn't fit in the archive
+    LocalHeaderMissing,
+    InvalidLocalSignature { found: u32 },
+    CompressionMethodMismatch { central: u16, local: u16 },
+    FilenameMismatch,
+    /// `local` is `None` if it's saturated, but there's no Zip64 extra field with it
+    CompressedSizeMismatch { central: u64, local: Option<u64> },
+    /// `local` is `None` if it's saturated, but there's no Zip64 extra field with it
+    DecompressedSizeMismatch { central: u64, local: Option<u64> },
+}
+
+impl core::fmt::Display for MismatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::DataDescriptorMissing => f.write_str("data descriptor is missing"),
+            Self::CrcFieldMismatch { central, other } =>
+                write!(f, "CRC {central:#010x} in central directory doesn't match {other:#010x}"),
+            Self::LocalHeaderMissing => f.write_str("local file header is missing"),
+            Self::InvalidLocalSignature { found } => write!(f, "local file header has invalid signature {found:#010x}"),
+            Self::CompressionMethodMismatch { central, local } =>
+                write!(f, "compression method {central} in central directory doesn't match {local}"),
+            Self::FilenameMismatch => f.write_str("filename in central directory doesn't match local file header"),
+            Self::CompressedSizeMismatch { central, local } =>
+                write!(f, "compressed size {central} in central directory doesn't match {local:?}"),
+            Self::DecompressedSizeMismatch { central, local } =>
+                write!(f, "decompressed size {central} in central directory doesn't match {local:?}"),
+        }
+    }
+}
+
+impl core::error::Error for MismatchError {}
+
+/// Summary of the archive, without dumping the buffer
+impl<'data> core::fmt::Debug for Zip<'data> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Zip")
+            .field("central_dir_records_total", &self.central_dir_records_total)
+            .field("central_dir_offset", &self.central_dir_offset)
+            .field("central_dir_size", &self.central_dir_size)
+            .field("prefix_len", &self.prefix_len)
+            .field("zip64", &self.zip64)
+            .field("name_encoding", &self.name_encoding)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'data> core::fmt::Debug for File<'data> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("File")
+            .field("decompressed_crc", &self.decompressed_crc)
+            .field("decompressed_size", &self.decompressed_size)
+            .field("compression_method", &self.compression_method)
+            .field("extra_fields", &self.extra_fields)
+            .field("filename", &self.filename)
+            .field("comment", &self.comment)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Without the archive itself, which would be the whole buffer
+impl<'a> core::fmt::Debug for NonStrictIter<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NonStrictIter")
+            .field("offset", &self.offset)
+            .field("name_encoding", &self.name_encoding)
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
 }
 
 fn slice_split_at<T>(s: &[T], index: usize) -> Option<(&[T], &[T])> {
@@ -89,6 +1490,19 @@ fn slice_split_at<T>(s: &[T], index: usize) -> Option<(&[T], &[T])> {
     };
 }
 
+/// Reads little-endian values from the front of `bytes`, advancing it
+fn take_u32(bytes: &mut &[u8]) -> Option<u32> {
+    let (value, tail) = slice_split_at(bytes, 4)?;
+    *bytes = tail;
+    return Some(u32::from_le_bytes(value.try_into().ok()?));
+}
+
+fn take_u64(bytes: &mut &[u8]) -> Option<u64> {
+    let (value, tail) = slice_split_at(bytes, 8)?;
+    *bytes = tail;
+    return Some(u64::from_le_bytes(value.try_into().ok()?));
+}
+
 /// Iterator over CentralDirIter.
 ///
 /// It is not strict, meaning that if for example signature is invalid or there
@@ -96,84 +1510,342 @@ fn slice_split_at<T>(s: &[T], index: usize) -> Option<(&[T], &[T])> {
 ///
 /// On iteration, it returns a `(central dir, additional data slice)` tuple, so if you need
 /// for example filename, you need to grab it yourself
-/* TODO: make a stricter version of this iterator, that fails on these
- * - signature doesn't match
- * - directory has too much / too little records 
- * - some other weird stuff */
+///
+/// See `StrictIter` for the version that fails on these.
+///
+/// It's `Copy`, so it can be snapshotted and resumed later.
+#[derive(Clone, Copy)]
 pub struct NonStrictIter<'a> {
     pub data: &'a [u8],
     pub offset: usize,
-
-    //pub _remaining_items: usize,
+    pub name_encoding: NameEncoding,
+    /// Records left to read, iteration stops at zero even if more follow
+    pub remaining: u64,
 }
 
-impl<'a> Iterator for NonStrictIter<'a> {
-    type Item = File<'a>;
+/// Central directory record together with its variable length fields.
+/// Sizes and local header offset are already taken from the Zip64 extra field if present.
+struct CentralRecord<'a> {
+    header: &'a raw::CentralDirectoryFileHeader,
+    filename: &'a [u8],
+    extra_fields: &'a [u8],
+    comment: &'a [u8],
+    has_zip64_extra: bool,
+    local_file_offset: usize,
+    compressed_size: u64,
+    decompressed_size: u64,
+    /// Length of the whole record in bytes
+    len: usize,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        const CENTRAL_DIR_HEADER_SIZE: usize = mem::size_of::<raw::CentralDirectoryFileHeader>();
+impl<'a> CentralRecord<'a> {
+    const HEADER_SIZE: usize = mem::size_of::<raw::CentralDirectoryFileHeader>();
 
-        let bytes = self.data.get(self.offset..)?;
-        let (central_dir, bytes) = slice_split_at(bytes, CENTRAL_DIR_HEADER_SIZE)?;
-        let central_dir = central_dir.as_ptr() as *const raw::CentralDirectoryFileHeader;
-        /* SAFETY: Again something that `bytemuck` crate would handle nicer, but in
-         * the same way - we have enough bytes and they're aligned enough to be casted */
-        let central_dir = unsafe { &*central_dir };
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let (central_dir, bytes) = slice_split_at(bytes, Self::HEADER_SIZE)?;
+        let central_dir: &raw::CentralDirectoryFileHeader = raw::from_prefix(central_dir)?;
 
         //debug_assert_eq!({central_dir.signature}, CENTRAL_DIR_HEADER_SIGNATURE);
 
-        let mut local_file_offset = central_dir.local_file_header_offset as usize;
-        let mut compressed_size = central_dir.compressed_size as usize;
-        let mut decompressed_size = central_dir.decompressed_size as usize;
-        let filename_len = central_dir.filename_len as usize;
-        let extra_fields_len = central_dir.extra_field_len as usize;
-        let file_comment_len = central_dir.file_comment_len as usize;
+        let mut local_file_offset = central_dir.local_file_header_offset.get() as usize;
+        let mut compressed_size = central_dir.compressed_size.get() as u64;
+        let mut decompressed_size = central_dir.decompressed_size.get() as u64;
+        let filename_len = central_dir.filename_len.get() as usize;
+        let extra_fields_len = central_dir.extra_field_len.get() as usize;
+        let file_comment_len = central_dir.file_comment_len.get() as usize;
 
         let (filename, bytes) = slice_split_at(bytes, filename_len)?;
-        let (extra_fields, bytes) = slice_split_at(bytes, extra_fields_len)?; // TODO: parse extra fields
+        let (extra_fields, bytes) = slice_split_at(bytes, extra_fields_len)?;
         let (comment, _bytes) = slice_split_at(bytes, file_comment_len)?;
 
         let mut extra_iter = extra::Iter { data: extra_fields };
-        if let Some(zip64) = extra_iter
+        let zip64 = extra_iter
             .find(|(signature, _)| *signature == extra::Zip64::SIGNATURE)
-            .and_then(|(_, data)| extra::Zip64::parse(data))
-        {
-            compressed_size = zip64.compressed_size as usize;
-            decompressed_size = zip64.decompressed_size as usize;
-            local_file_offset = zip64.local_header_record_offset as usize;
-        }
-
-        let total_bytes_len =
-            CENTRAL_DIR_HEADER_SIZE +
-            filename_len as usize +
-            extra_fields_len as usize +
-            file_comment_len as usize;
-
-        self.offset += total_bytes_len;
-
-        let local_file_header = self.data.get(local_file_offset..)
-            .and_then(|slice| slice.get(.. mem::size_of::<raw::LocalFileHeader>()))?;
-        let local_file_header = local_file_header.as_ptr() as *const raw::LocalFileHeader;
-        /* SAFETY: Again something that `bytemuck` crate would handle nicer, but in
-         * the same way - we have enough bytes and they're aligned enough to be casted */
-        let local_file_header = unsafe { &*local_file_header };
-        let packed_file_offset = local_file_offset +
-            local_file_header.filename_len as usize +
-            local_file_header.extra_field_len as usize;
-
-        let bytes = self.data.get(packed_file_offset..)
-            .and_then(|slice| slice.get(..compressed_size))?;
+            .and_then(|(_, data)| extra::Zip64::parse_with_header(data, central_dir));
+        let has_zip64_extra = zip64.is_some();
+        if let Some(zip64) = zip64 {
+            compressed_size = zip64.compressed_size;
+            decompressed_size = zip64.decompressed_size;
+            /* Can't be in the buffer if it doesn't fit in usize,
+             * so past the end is as good as any other out of bounds offset */
+            local_file_offset = usize::try_from(zip64.local_header_record_offset).unwrap_or(usize::MAX);
+        }
 
-        let file = File {
-            compression_method: CompressionMethod::from_u16(central_dir.compression_method)?,
-            decompressed_crc: central_dir.decompressed_crc,
-            decompressed_size,
-            extra_fields,
+        let len =
+            Self::HEADER_SIZE +
+            filename_len +
+            extra_fields_len +
+            file_comment_len;
+
+        return Some(Self {
+            header: central_dir,
             filename,
+            extra_fields,
             comment,
-            bytes,
+            has_zip64_extra,
+            local_file_offset,
+            compressed_size,
+            decompressed_size,
+            len,
+        });
+    }
+}
+
+impl<'a> CentralRecord<'a> {
+    /// Makes a `File` out of this record, `data` being the whole archive.
+    /// Fails on unknown compression method, or when decompressed size doesn't
+    /// fit in `usize`.
+    fn into_file(self, data: &'a [u8], name_encoding: NameEncoding) -> Option<File<'a>> {
+        /* Record was parsed from `data`, so this is where it starts */
+        let central_record_offset = self.header as *const _ as usize - data.as_ptr() as usize;
+        let file = File {
+            compression_method: CompressionMethod::from_u16(self.header.compression_method.get())?,
+            decompressed_crc: self.header.decompressed_crc.get(),
+            decompressed_size: usize::try_from(self.decompressed_size).ok()?,
+            extra_fields: self.extra_fields,
+            filename: self.filename,
+            comment: self.comment,
+            header: *self.header,
+            compressed_size: self.compressed_size,
+            decompressed_size_u64: self.decompressed_size,
+            has_zip64_extra: self.has_zip64_extra,
+            name_encoding,
+            data,
+            local_file_offset: self.local_file_offset,
+            central_record_offset: Some(central_record_offset),
         };
 
         return Some(file);
     }
 }
+
+fn local_file_header(data: &[u8], offset: usize) -> Option<&raw::LocalFileHeader> {
+    return raw::from_prefix(data.get(offset..)?);
+}
+
+impl<'a> Iterator for NonStrictIter<'a> {
+    type Item = File<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        /* Records with an unknown compression method are skipped,
+         * there's nothing to do with them anyway. Same for ones too big
+         * for this target's usize. */
+        while self.remaining > 0 {
+            let Some(record) = self.data.get(self.offset..).and_then(CentralRecord::parse) else {
+                self.remaining = 0;
+                return None;
+            };
+            self.offset += record.len;
+            self.remaining -= 1;
+            if let Some(file) = record.into_file(self.data, self.name_encoding) {
+                return Some(file);
+            }
+        }
+
+        return None;
+    }
+
+    /// Any of the remaining records can be skipped or end the iteration,
+    /// so only the upper bound is known
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        return (0, Some(remaining));
+    }
+}
+
+/// Iterator with one item per record the end record declares, so its length
+/// is exact, unlike `NonStrictIter`'s.
+///
+/// Records that can't be made into a `File` (unknown compression method,
+/// size too big for this target) give `None`. So do all the records after
+/// one that doesn't fit in the archive, since there's no telling where they are.
+#[derive(Clone, Copy)]
+pub struct EntryIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+    name_encoding: NameEncoding,
+    remaining: u64,
+    /// Hit a record that doesn't fit, everything after it is `None`
+    broken: bool,
+}
+
+impl<'a> Iterator for EntryIter<'a> {
+    type Item = Option<File<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if self.broken {
+            return Some(None);
+        }
+
+        let Some(record) = self.data.get(self.offset..).and_then(CentralRecord::parse) else {
+            self.broken = true;
+            return Some(None);
+        };
+        self.offset += record.len;
+        return Some(record.into_file(self.data, self.name_encoding));
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        return (remaining, Some(remaining));
+    }
+}
+
+/// Length is the number of records left according to the end record,
+/// which on 32-bit targets may not fit in `usize` for hostile Zip64 archives
+impl<'a> ExactSizeIterator for EntryIter<'a> {}
+
+/// Iterator over files in the central directory, which reports malformed
+/// records instead of silently stopping or accepting them.
+///
+/// It stops after the first error or after the declared number of records.
+/// If there are more records than declared, that's reported as the last item.
+pub struct StrictIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+    declared: u64,
+    remaining: u64,
+    name_encoding: NameEncoding,
+    failed: bool,
+}
+
+impl<'a> StrictIter<'a> {
+    fn signature_at(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..)?.get(..4)?;
+        return Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+    }
+
+    fn next_file(&mut self) -> Result<File<'a>, StrictError> {
+        let offset = self.offset;
+
+        /* Running into the end record means the directory is shorter than declared */
+        match self.signature_at(offset) {
+            Some(raw::CENTRAL_DIR_HEADER_SIGNATURE) => {},
+            Some(raw::CENTRAL_DIR_END_SIGNATURE | raw::CENTRAL_DIR_END_SIGNATURE_ZIP64) =>
+                return Err(StrictError::RecordCountMismatch {
+                    declared: self.declared,
+                    found: self.declared - self.remaining,
+                }),
+            Some(_) => return Err(StrictError::InvalidSignature { offset }),
+            None => return Err(StrictError::Malformed { offset }),
+        }
+
+        let record = self.data.get(offset..)
+            .and_then(CentralRecord::parse)
+            .ok_or(StrictError::Malformed { offset })?;
+
+        /* Every real entry has a name, zeroed length is a common corruption */
+        if record.filename.is_empty() {
+            return Err(StrictError::EmptyFilename { offset });
+        }
+
+        let local_header = local_file_header(self.data, record.local_file_offset)
+            .filter(|header| header.signature.get() == raw::LOCAL_FILE_HEADER_SIGNATURE)
+            .ok_or(StrictError::InvalidLocalSignature {
+                offset,
+                local_file_offset: record.local_file_offset,
+            })?;
+
+        /* Saturating, so that a header running past the end also counts as truncated */
+        let data_offset = record.local_file_offset
+            .saturating_add(mem::size_of::<raw::LocalFileHeader>())
+            .saturating_add(local_header.filename_len.get() as usize)
+            .saturating_add(local_header.extra_field_len.get() as usize);
+        let available = self.data.len().saturating_sub(data_offset);
+        if record.compressed_size > available as u64 {
+            return Err(StrictError::TruncatedData { offset, needed: record.compressed_size, available });
+        }
+
+        self.offset += record.len;
+        self.remaining -= 1;
+
+        if usize::try_from(record.decompressed_size).is_err() {
+            return Err(StrictError::SizeOverflow { offset, size: record.decompressed_size });
+        }
+
+        let method = record.header.compression_method.get();
+        return record.into_file(self.data, self.name_encoding)
+            .ok_or(StrictError::UnknownCompressionMethod { offset, method });
+    }
+}
+
+impl<'a> Iterator for StrictIter<'a> {
+    type Item = Result<File<'a>, StrictError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        if self.remaining == 0 {
+            self.failed = true;
+            let mut extra = 0;
+            while self.signature_at(self.offset) == Some(raw::CENTRAL_DIR_HEADER_SIGNATURE) {
+                let Some(record) = CentralRecord::parse(&self.data[self.offset..]) else {
+                    break;
+                };
+                self.offset += record.len;
+                extra += 1;
+            }
+            if extra == 0 {
+                return None;
+            }
+            return Some(Err(StrictError::RecordCountMismatch {
+                declared: self.declared,
+                found: self.declared + extra,
+            }));
+        }
+
+        let result = self.next_file();
+        self.failed = result.is_err();
+        return Some(result);
+    }
+}
+
+/// Reason why `StrictIter` rejected a central directory record at `offset`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StrictError {
+    /// Record doesn't fit in the archive
+    Malformed { offset: usize },
+    /// Record uses a compression method `CompressionMethod` doesn't know
+    UnknownCompressionMethod { offset: usize, method: u16 },
+    /// Decompressed size doesn't fit in `usize`, only possible on 32-bit targets
+    SizeOverflow { offset: usize, size: u64 },
+    /// Record has zero-length filename
+    EmptyFilename { offset: usize },
+    /// Record doesn't start with the central directory header signature
+    InvalidSignature { offset: usize },
+    /// Local file header the record points to has a wrong signature or doesn't fit in the archive
+    InvalidLocalSignature { offset: usize, local_file_offset: usize },
+    /// Compressed data is longer than what's left of the archive, usually a partial download
+    TruncatedData { offset: usize, needed: u64, available: usize },
+    /// Central directory has a different number of records than the end record says
+    RecordCountMismatch { declared: u64, found: u64 },
+}
+
+impl core::fmt::Display for StrictError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Malformed { offset } => write!(f, "malformed central directory record at offset {offset}"),
+            Self::UnknownCompressionMethod { offset, method } =>
+                write!(f, "central directory record at offset {offset} has unknown compression method {method}"),
+            Self::SizeOverflow { offset, size } =>
+                write!(f, "central directory record at offset {offset} declares size {size}, which doesn't fit in usize"),
+            Self::EmptyFilename { offset } => write!(f, "central directory record at offset {offset} has no filename"),
+            Self::InvalidSignature { offset } => write!(f, "central directory record at offset {offset} has invalid signature"),
+            Self::InvalidLocalSignature { offset, local_file_offset } =>
+                write!(f, "central directory record at offset {offset} points to invalid local file header at offset {local_file_offset}"),
+            Self::TruncatedData { offset, needed, available } =>
+                write!(f, "central directory record at offset {offset} has {needed} bytes of data, but only {available} bytes are left"),
+            Self::RecordCountMismatch { declared, found } =>
+                write!(f, "central directory has {found} records, but {declared} were declared"),
+        }
+    }
+}
+
+impl core::error::Error for StrictError {}
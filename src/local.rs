@@ -0,0 +1,221 @@
+//! Walking local file headers from the start of the archive, for when the
+//! central directory is truncated or missing, like in an interrupted download.
+
+use core::mem;
+
+use crate::{raw, extra, CompressionMethod, File, NameEncoding};
+use crate::descriptor::DataDescriptor;
+
+/// Iterator over files found by following local file headers one after another,
+/// a fallback for when `Zip::new` fails.
+///
+/// Everything comes from the local headers, so there are no comments and
+/// no attributes. It stops at the first thing that isn't a local file header.
+///
+/// Entries with a data descriptor (general purpose flag bit 3) usually have
+/// zeroed sizes and CRC in the local header, so there's no reliable way to
+/// find where their data ends. Such an entry is still yielded, with whatever
+/// the local header says, but the iteration ends after it.
+pub struct LocalHeaderIter<'data> {
+    data: &'data [u8],
+    offset: usize,
+    name_encoding: NameEncoding,
+    done: bool,
+    /// Look for data descriptors to find the sizes of streamed entries, see `RecoverIter`
+    recover: bool,
+}
+
+impl<'data> LocalHeaderIter<'data> {
+    pub fn new(data: &'data [u8]) -> Self {
+        return Self::with_name_encoding(data, NameEncoding::Auto);
+    }
+
+    pub fn with_name_encoding(data: &'data [u8], name_encoding: NameEncoding) -> Self {
+        return Self { data, offset: 0, name_encoding, done: false, recover: false };
+    }
+
+    /// Offset of the next local file header to read
+    pub fn offset(&self) -> usize {
+        return self.offset;
+    }
+
+    /// Parses the local header at the current offset and moves past its data.
+    /// `None` on unknown compression method or size not fitting in `usize`.
+    fn next_entry(&mut self) -> Option<Option<File<'data>>> {
+        let local_file_offset = self.offset;
+        let local: &raw::LocalFileHeader = raw::from_prefix(self.data.get(local_file_offset..)?)?;
+        if local.signature.get() != raw::LOCAL_FILE_HEADER_SIGNATURE {
+            return None;
+        }
+
+        let name_offset = local_file_offset + mem::size_of::<raw::LocalFileHeader>();
+        let (filename, rest) = crate::slice_split_at(self.data.get(name_offset..)?, local.filename_len.get() as usize)?;
+        let (extra_fields, _) = crate::slice_split_at(rest, local.extra_field_len.get() as usize)?;
+
+        /* Zip64 extra field in the local header always has both sizes */
+        let mut zip64 = extra::Iter { data: extra_fields }
+            .find(|&(signature, _)| signature == <extra::Zip64 as extra::Extra>::SIGNATURE)
+            .map(|(_, field)| field);
+        let has_zip64_extra = zip64.is_some();
+        let mut size = |size: u32| match (size, zip64.as_mut()) {
+            (u32::MAX, Some(field)) => crate::take_u64(field),
+            (size, _) => Some(size as u64),
+        };
+        let mut decompressed_size = size(local.uncompressed_size.get())?;
+        let mut compressed_size = size(local.compressed_size.get())?;
+        let mut crc = local.uncompressed_crc.get();
+
+        let data_offset = name_offset + filename.len() + extra_fields.len();
+        let streamed = crate::GeneralPurposeFlags(local.flags.get()).has_data_descriptor();
+        let mut descriptor_len = 0;
+        if self.recover && streamed {
+            /* A descriptor disagreeing with the local header about where the data
+             * ends could be anything, and following it would land in the middle
+             * of something, so it's ignored */
+            let descriptor = if compressed_size == 0 {
+                find_descriptor(self.data, data_offset, has_zip64_extra)
+            } else {
+                usize::try_from(compressed_size).ok()
+                    .and_then(|size| data_offset.checked_add(size))
+                    .and_then(|end| DataDescriptor::parse(self.data.get(end..)?, has_zip64_extra))
+                    .filter(|descriptor| descriptor.compressed_size == compressed_size)
+            };
+            if let Some(descriptor) = descriptor {
+                crc = descriptor.crc;
+                compressed_size = descriptor.compressed_size;
+                decompressed_size = descriptor.decompressed_size;
+                descriptor_len = descriptor.len;
+            }
+        }
+
+        let data_end = usize::try_from(compressed_size).ok()
+            .and_then(|size| data_offset.checked_add(size))
+            .filter(|&end| end <= self.data.len())?;
+
+        let header = raw::CentralDirectoryFileHeader {
+            signature: raw::Le32(raw::CENTRAL_DIR_HEADER_SIGNATURE.to_le_bytes()),
+            version_made_by: raw::Le16([0; 2]),
+            version_min: local.version_min,
+            flags: local.flags,
+            compression_method: local.compression_method,
+            last_mod_time: local.last_mod_time,
+            last_mod_date: local.last_mod_date,
+            decompressed_crc: raw::Le32(crc.to_le_bytes()),
+            compressed_size: raw::Le32(saturate(compressed_size).to_le_bytes()),
+            decompressed_size: raw::Le32(saturate(decompressed_size).to_le_bytes()),
+            filename_len: local.filename_len,
+            extra_field_len: local.extra_field_len,
+            file_comment_len: raw::Le16([0; 2]),
+            disk_number: raw::Le16([0; 2]),
+            file_attr_internal: raw::Le16([0; 2]),
+            file_attr_external: raw::Le32([0; 4]),
+            local_file_header_offset: raw::Le32(saturate(local_file_offset as u64).to_le_bytes()),
+        };
+
+        let method = CompressionMethod::from_u16(local.compression_method.get());
+        let size = usize::try_from(decompressed_size).ok();
+        let file = method.zip(size).map(|(compression_method, size)| File {
+            decompressed_crc: crc,
+            decompressed_size: size,
+            compression_method,
+            extra_fields,
+            filename,
+            comment: &[],
+            header,
+            compressed_size,
+            decompressed_size_u64: decompressed_size,
+            has_zip64_extra,
+            name_encoding: self.name_encoding,
+            data: self.data,
+            local_file_offset,
+            central_record_offset: None,
+        });
+
+        /* Without the descriptor there's no telling where the next header is */
+        if streamed && descriptor_len == 0 {
+            self.done = true;
+        }
+        self.offset = data_end + descriptor_len;
+        return Some(file);
+    }
+}
+
+impl<'data> Iterator for LocalHeaderIter<'data> {
+    type Item = File<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        /* Entries that can't be made into a `File` are skipped, like in `NonStrictIter` */
+        while !self.done {
+            match self.next_entry() {
+                Some(Some(file)) => return Some(file),
+                Some(None) => continue,
+                None => self.done = true,
+            }
+        }
+
+        return None;
+    }
+}
+
+/// Like `LocalHeaderIter`, but it also gets past entries with a data descriptor,
+/// salvaging what it can from archives with a corrupt central directory.
+///
+/// When the local header of such an entry has zero compressed size, the data is
+/// scanned for a data descriptor signature (`0x08074b50`) whose compressed size
+/// matches the distance from the start of the data. Sizes and CRC are then taken
+/// from the descriptor. Descriptors without the signature can't be found this way,
+/// so the iteration ends after such an entry, as in `LocalHeaderIter`. It also ends
+/// after an entry whose local header has a nonzero compressed size that the
+/// descriptor after the data disagrees with.
+///
+/// Created by `Zip::recover`.
+pub struct RecoverIter<'data>(LocalHeaderIter<'data>);
+
+impl<'data> RecoverIter<'data> {
+    pub fn new(data: &'data [u8]) -> Self {
+        return Self::with_name_encoding(data, NameEncoding::Auto);
+    }
+
+    pub fn with_name_encoding(data: &'data [u8], name_encoding: NameEncoding) -> Self {
+        let mut iter = LocalHeaderIter::with_name_encoding(data, name_encoding);
+        iter.recover = true;
+        return Self(iter);
+    }
+
+    /// Offset of the next local file header to read
+    pub fn offset(&self) -> usize {
+        return self.0.offset();
+    }
+}
+
+impl<'data> Iterator for RecoverIter<'data> {
+    type Item = File<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.0.next();
+    }
+}
+
+/// Finds the first data descriptor after `data_offset` that has a signature
+/// and a compressed size pointing back at `data_offset`
+fn find_descriptor(data: &[u8], data_offset: usize, zip64: bool) -> Option<DataDescriptor> {
+    let signature = raw::DATA_DESCRIPTOR_SIGNATURE.to_le_bytes();
+    let data_len = data.len();
+    let mut offset = data_offset;
+    while offset < data_len {
+        let position = data[offset..].windows(4).position(|window| window == signature)?;
+        offset += position;
+        let descriptor = DataDescriptor::parse(&data[offset..], zip64);
+        if descriptor.is_some_and(|descriptor| descriptor.compressed_size == (offset - data_offset) as u64) {
+            return descriptor;
+        }
+        offset += 1;
+    }
+
+    return None;
+}
+
+/// Size for a 32-bit header field, `u32::MAX` if it doesn't fit like in Zip64 entries
+fn saturate(size: u64) -> u32 {
+    return u32::try_from(size).unwrap_or(u32::MAX);
+}
@@ -0,0 +1,36 @@
+//! Checking filenames before using them as paths on disk.
+//!
+//! Filenames come straight from the archive, so they can point anywhere
+//! (`../../etc/passwd`, `/etc/passwd`, `C:\Windows`...), which is known as Zip Slip.
+
+/// Returns `name` if it's a relative path that stays inside the directory
+/// it's extracted to, on both Unix and Windows.
+///
+/// Rejects (returning `None`):
+/// - absolute paths and Windows drive prefixes (`C:`, `\\server`)
+/// - any `..` component
+/// - backslashes, which are separators on Windows but not in ZIP
+/// - NUL bytes, which truncate paths in C APIs
+/// - empty names and names that aren't valid UTF-8
+pub fn sanitize(name: &[u8]) -> Option<&str> {
+    let name = core::str::from_utf8(name).ok()?;
+
+    if name.is_empty() || name.starts_with('/') {
+        return None;
+    }
+    if name.contains(['\\', '\0']) {
+        return None;
+    }
+    /* Drive letter, which makes the path absolute (`C:/`) or relative to
+     * the drive's current directory (`C:x`) on Windows */
+    if let [letter, b':', ..] = name.as_bytes() {
+        if letter.is_ascii_alphabetic() {
+            return None;
+        }
+    }
+    if name.split('/').any(|component| component == "..") {
+        return None;
+    }
+
+    return Some(name);
+}
@@ -3,88 +3,190 @@ use core::ops::Range;
 
 pub const CENTRAL_DIR_END_SIGNATURE: u32 = 0x06054b50;
 pub const CENTRAL_DIR_END_SIGNATURE_ZIP64: u32 = 0x06064b50;
+pub const CENTRAL_DIR_END_LOCATOR_SIGNATURE_ZIP64: u32 = 0x07064b50;
 pub const CENTRAL_DIR_HEADER_SIGNATURE: u32 = 0x02014b50;
+pub const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+pub const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+/// First bytes of a split or spanned archive, same as the data descriptor signature
+pub const SPANNING_SIGNATURE: u32 = 0x08074b50;
+/// First bytes of an archive that was meant to be spanned, but fit on one disk ("PK00")
+pub const TEMPORARY_SPANNING_SIGNATURE: u32 = 0x30304b50;
+
+/// Little-endian integer as laid out on disk.
+/// ZIP is little-endian everywhere, so fields are read through `get`
+/// instead of relying on the host's byte order.
+macro_rules! le_int {
+    ($name:ident, $int:ty, $size:literal) => {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+        #[repr(transparent)]
+        pub struct $name(pub [u8; $size]);
+
+        impl $name {
+            pub const fn get(self) -> $int {
+                return <$int>::from_le_bytes(self.0);
+            }
+        }
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                return self.get().fmt(f);
+            }
+        }
+    };
+}
+
+le_int!(Le16, u16, 2);
+le_int!(Le32, u32, 4);
+le_int!(Le64, u64, 8);
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 #[repr(C, packed)]
 pub struct LocalFileHeader {
-    pub signature: u32, // 0x04034b50
-    pub version_min: u16,
-    pub flags: u16,
-    pub compression_method: u16,
-    pub last_mod_time: u16,
-    pub last_mod_date: u16,
-    pub uncompressed_crc: u32,
-    pub compressed_size: u32,
-    pub uncompressed_size: u32,
-    pub filename_len: u16,
-    pub extra_field_len: u16,
+    pub signature: Le32, // 0x04034b50
+    pub version_min: Le16,
+    pub flags: Le16,
+    pub compression_method: Le16,
+    pub last_mod_time: Le16,
+    pub last_mod_date: Le16,
+    pub uncompressed_crc: Le32,
+    pub compressed_size: Le32,
+    pub uncompressed_size: Le32,
+    pub filename_len: Le16,
+    pub extra_field_len: Le16,
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 #[repr(C, packed)]
 pub struct CentralDirectoryFileHeader {
-    pub signature: u32, // 0x02014b50
-    pub version_made_by: u16,
-    pub version_min: u16,
-    pub flags: u16,
-    pub compression_method: u16,
-    pub last_mod_time: u16,
-    pub last_mod_date: u16,
-    pub decompressed_crc: u32,
-    pub compressed_size: u32,
-    pub decompressed_size: u32,
-    pub filename_len: u16,
-    pub extra_field_len: u16,
-    pub file_comment_len: u16,
-    pub disk_number: u16,
-    pub file_attr_internal: u16,
-    pub file_attr_external: u32,
-    pub local_file_header_offset: u32,
+    pub signature: Le32, // 0x02014b50
+    pub version_made_by: Le16,
+    pub version_min: Le16,
+    pub flags: Le16,
+    pub compression_method: Le16,
+    pub last_mod_time: Le16,
+    pub last_mod_date: Le16,
+    pub decompressed_crc: Le32,
+    pub compressed_size: Le32,
+    pub decompressed_size: Le32,
+    pub filename_len: Le16,
+    pub extra_field_len: Le16,
+    pub file_comment_len: Le16,
+    pub disk_number: Le16,
+    pub file_attr_internal: Le16,
+    pub file_attr_external: Le32,
+    pub local_file_header_offset: Le32,
 }
 
 /// The actual ZIP "header"
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 #[repr(C, packed)]
 pub struct CentralDirectoryRecordEnd {
-    pub signature: u32, // 0x06054b50
-    pub disk_num: u16,
-    pub central_dir_start_disk: u16,
-    pub central_dir_records_on_this_disk: u16,
-    pub central_dir_records_total: u16,
-    pub central_dir_size: u32,
-    pub central_dir_offset: u32,
-    pub comment_length: u16,
+    pub signature: Le32, // 0x06054b50
+    pub disk_num: Le16,
+    pub central_dir_start_disk: Le16,
+    pub central_dir_records_on_this_disk: Le16,
+    pub central_dir_records_total: Le16,
+    pub central_dir_size: Le32,
+    pub central_dir_offset: Le32,
+    pub comment_length: Le16,
 }
 
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 #[repr(C, packed)]
 pub struct ExtraHeader {
-    pub header: u16,
-    pub extra_field_size: u16,
+    pub header: Le16,
+    pub extra_field_size: Le16,
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 #[repr(C, packed)]
 pub struct Zip64Extra {
-    pub decompressed_size: u64,
-    pub compressed_size: u64,
-    pub local_header_record_offset: u64,
-    pub disk_no: u32,
+    pub decompressed_size: Le64,
+    pub compressed_size: Le64,
+    pub local_header_record_offset: Le64,
+    pub disk_no: Le32,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C, packed)]
+pub struct Zip64CentralDirectoryLocator {
+    pub signature: Le32, // 0x07064b50
+    pub central_dir_end_disk: Le32,
+    pub central_dir_end_offset: Le64,
+    pub total_disks: Le32,
 }
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
 #[repr(C, packed)]
 pub struct Zip64CentralDirectoryRecordEnd {
-    pub signature: u32, // 0x06064b50
-    pub self_size: u64,
-    pub version_made_by: u16,
-    pub version_min: u16,
-    pub disk_num: u32,
-    pub central_dir_start_disk: u32,
-    pub central_dir_records_on_this_disk: u64,
-    pub central_dir_records_total: u64,
-    pub central_directory_size: u64,
-    pub central_dir_offset: u64,
+    pub signature: Le32, // 0x06064b50
+    pub self_size: Le64,
+    pub version_made_by: Le16,
+    pub version_min: Le16,
+    pub disk_num: Le32,
+    pub central_dir_start_disk: Le32,
+    pub central_dir_records_on_this_disk: Le64,
+    pub central_dir_records_total: Le64,
+    pub central_directory_size: Le64,
+    pub central_dir_offset: Le64,
+}
+
+/// Struct that can be read straight from archive bytes.
+///
+/// # Safety
+/// Without the `bytemuck` feature, implementors promise what `bytemuck::Pod`
+/// would check: any bit pattern is valid, there's no padding and the
+/// alignment is 1, so it can be cast from any position in the archive.
+#[cfg(feature = "bytemuck")]
+pub unsafe trait Raw: bytemuck::Pod {}
+/// Struct that can be read straight from archive bytes.
+///
+/// # Safety
+/// Implementors promise what `bytemuck::Pod` would check: any bit pattern
+/// is valid, there's no padding and the alignment is 1, so it can be cast
+/// from any position in the archive.
+#[cfg(not(feature = "bytemuck"))]
+pub unsafe trait Raw: Copy {}
+
+/* SAFETY: all of these are `packed` and made only of byte arrays.
+ * The asserts make sure the alignment stays at 1. */
+macro_rules! impl_raw {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl Raw for $t {}
+            const _: () = assert!(mem::align_of::<$t>() == 1);
+        )*
+    };
+}
+
+impl_raw!(
+    LocalFileHeader,
+    CentralDirectoryFileHeader,
+    CentralDirectoryRecordEnd,
+    ExtraHeader,
+    Zip64Extra,
+    Zip64CentralDirectoryLocator,
+    Zip64CentralDirectoryRecordEnd
+);
+
+/// Reinterprets the start of `bytes` as `T`, or `None` if there's not enough of them
+pub fn from_prefix<T: Raw>(bytes: &[u8]) -> Option<&T> {
+    let bytes = bytes.get(..mem::size_of::<T>())?;
+
+    #[cfg(feature = "bytemuck")]
+    return bytemuck::try_from_bytes(bytes).ok();
+
+    /* SAFETY: guaranteed by `Raw`, and we have exactly enough bytes */
+    #[cfg(not(feature = "bytemuck"))]
+    return Some(unsafe { &*(bytes.as_ptr() as *const T) });
 }
 
 impl CentralDirectoryRecordEnd {
@@ -94,28 +196,112 @@ impl CentralDirectoryRecordEnd {
     /// Because it is possible to embed a valid zip header inside zip header comment,
     /// this tries to find the topmost header.
     /// This returns reference to CentralDirectoryRecordEnd and length of the comment
+    ///
+    /// A header whose comment reaches exactly to the end of the buffer is preferred.
+    /// If there's none, like when some tool padded the archive, this takes
+    /// the topmost header whose comment at least fits in the buffer.
     pub fn find(bytes: &[u8]) -> Option<(&Self, usize)> {
-        let start_offset = bytes.len().saturating_sub(u16::MAX as usize);
+        return Self::search(bytes, u16::MAX as usize + Self::SELF_SIZE, false);
+    }
+
+    /// Headers with a valid signature, from the topmost one, together with
+    /// the number of bytes following each of them.
+    ///
+    /// Instead of reinterpreting every window as a header, this looks for
+    /// the first byte of the signature with `memrchr` and only checks the rest of it there,
+    /// which keeps long comments cheap to skip.
+    fn candidates(bytes: &[u8]) -> impl Iterator<Item=(usize, &Self)> {
+        const SIGNATURE: [u8; 4] = CENTRAL_DIR_END_SIGNATURE.to_le_bytes();
+
+        let mut end = bytes.len().saturating_sub(Self::SELF_SIZE - 1);
+        return core::iter::from_fn(move || {
+            loop {
+                let pos = memchr::memrchr(SIGNATURE[0], &bytes[..end])?;
+                end = pos;
+                if bytes[pos..pos+4] != SIGNATURE {
+                    continue;
+                }
+                /* `end` never lets `pos` get closer than SELF_SIZE to the end */
+                let header = from_prefix::<Self>(&bytes[pos..])?;
+                return Some((bytes.len() - pos - Self::SELF_SIZE, header));
+            }
+        });
+    }
+
+    /// Like `find`, but searches the last `limit` bytes instead of just the last
+    /// 64 KiB, so passing `bytes.len()` searches the whole buffer.
+    ///
+    /// If no header has a comment reaching exactly to the end of the buffer,
+    /// this falls back to the topmost header whose comment at least fits in it,
+    /// which finds archives followed by some junk, and then to the topmost
+    /// header at all, even if its comment would run past the end.
+    ///
+    /// This may look through the whole searched range, so for big buffers it
+    /// is much slower than `find` - use it only for recovery.
+    pub fn find_within(bytes: &[u8], limit: usize) -> Option<(&Self, usize)> {
+        return Self::search(bytes, limit, true);
+    }
+
+    /// Header in the last `limit` bytes, see `find_within`
+    fn search(bytes: &[u8], limit: usize, allow_overrun: bool) -> Option<(&Self, usize)> {
+        let start_offset = bytes.len().saturating_sub(limit);
         let bytes = &bytes[start_offset..];
+        let mut fallback = None;
+        let mut overrun = None;
 
-        /* SAFETY: Self is so-called "Plain Ol' Data", so we can cast from bytes
-         * to an actual struct pointer.
-         * `array_windows` cares about having enough bytes. */
-        bytes
-            .array_windows::<{Self::SELF_SIZE}>()
-            .rev()
-            .map(|window| unsafe { &*(window as *const _ as *const Self) })
-            .enumerate()
-            .filter(|&(_i, maybe_header)| maybe_header.signature == CENTRAL_DIR_END_SIGNATURE)
-            .filter(|&(i, maybe_header)| maybe_header.comment_length as usize == i)
-            .next()
-            .map(|t| (t.1, t.0))
+        for (i, maybe_header) in Self::candidates(bytes) {
+            let comment_length = maybe_header.comment_length.get() as usize;
+            if comment_length == i {
+                return Some((maybe_header, i));
+            }
+            if comment_length < i && fallback.is_none() {
+                fallback = Some((maybe_header, comment_length));
+            }
+            if allow_overrun && overrun.is_none() {
+                overrun = Some((maybe_header, comment_length));
+            }
+        }
+
+        return fallback.or(overrun);
+    }
+
+    /// Whether some field is saturated, meaning that the real value is in
+    /// the Zip64 end of central directory record
+    pub fn needs_zip64(&self) -> bool {
+        return self.disk_num.get() == u16::MAX
+            || self.central_dir_start_disk.get() == u16::MAX
+            || self.central_dir_records_on_this_disk.get() == u16::MAX
+            || self.central_dir_records_total.get() == u16::MAX
+            || self.central_dir_size.get() == u32::MAX
+            || self.central_dir_offset.get() == u32::MAX;
     }
 
     pub fn central_dir_range(&self) -> Range<usize> {
-        let offset = self.central_dir_offset as usize;
-        let size = self.central_dir_size as usize;
+        let offset = self.central_dir_offset.get() as usize;
+        let size = self.central_dir_size.get() as usize;
         offset .. offset+size
     }
 }
 
+impl Zip64CentralDirectoryLocator {
+    /// Locator sits right before the end record at `end_record_offset`
+    pub fn find(bytes: &[u8], end_record_offset: usize) -> Option<&Self> {
+        let offset = end_record_offset.checked_sub(mem::size_of::<Self>())?;
+        let locator: &Self = from_prefix(bytes.get(offset..)?)?;
+        if locator.signature.get() != CENTRAL_DIR_END_LOCATOR_SIGNATURE_ZIP64 {
+            return None;
+        }
+        return Some(locator);
+    }
+}
+
+impl Zip64CentralDirectoryRecordEnd {
+    /// Record at `offset`, if there is one
+    pub fn at(bytes: &[u8], offset: usize) -> Option<&Self> {
+        let record: &Self = from_prefix(bytes.get(offset..)?)?;
+        if record.signature.get() != CENTRAL_DIR_END_SIGNATURE_ZIP64 {
+            return None;
+        }
+        return Some(record);
+    }
+}
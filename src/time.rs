@@ -0,0 +1,52 @@
+//! MS-DOS date and time, which is how ZIP stores modification times
+
+/// Unpacked MS-DOS date and time. It has no time zone, usually it's local time
+/// of whoever made the archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DosDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DosDateTime {
+    /// Unpacks the bit layout: date is `yyyyyyym mmmddddd` with years since 1980,
+    /// time is `hhhhhmmm mmmsssss` with seconds in 2-second units.
+    /// Returns `None` for zeroed date, which means the time is not set.
+    pub const fn from_dos(date: u16, time: u16) -> Option<Self> {
+        if date == 0 {
+            return None;
+        }
+
+        return Some(Self {
+            year: 1980 + (date >> 9),
+            month: ((date >> 5) & 0xF) as u8,
+            day: (date & 0x1F) as u8,
+            hour: (time >> 11) as u8,
+            minute: ((time >> 5) & 0x3F) as u8,
+            second: ((time & 0x1F) * 2) as u8,
+        });
+    }
+
+    /// Whether all fields are in range, since nothing stops an archive
+    /// from storing 31st of February or 25 o'clock
+    pub const fn is_valid(&self) -> bool {
+        return self.month >= 1 && self.month <= 12
+            && self.day >= 1 && self.day <= 31
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60;
+    }
+
+    /// As a `time` date, `None` if some field is out of range
+    #[cfg(feature = "time")]
+    pub fn to_primitive(&self) -> Option<::time::PrimitiveDateTime> {
+        let month = ::time::Month::try_from(self.month).ok()?;
+        let date = ::time::Date::from_calendar_date(self.year as i32, month, self.day).ok()?;
+        let time = ::time::Time::from_hms(self.hour, self.minute, self.second).ok()?;
+        return Some(::time::PrimitiveDateTime::new(date, time));
+    }
+}
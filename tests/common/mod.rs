@@ -0,0 +1,251 @@
+#![allow(dead_code)]
+
+/// Minimal ZIP writer for building synthetic archives in tests.
+/// It writes whatever it's told to, so it's also handy for building broken archives.
+#[derive(Clone, Default)]
+pub struct Entry {
+    pub name: Vec<u8>,
+    /// Bytes as stored in the archive (already compressed)
+    pub data: Vec<u8>,
+    pub method: u16,
+    pub crc: u32,
+    pub decompressed_size: u32,
+    pub flags: u16,
+    pub version_made_by: u16,
+    pub version_needed: u16,
+    pub last_mod_time: u16,
+    pub last_mod_date: u16,
+    pub internal_attr: u16,
+    pub external_attr: u32,
+    pub central_extra: Vec<u8>,
+    pub local_extra: Vec<u8>,
+    pub comment: Vec<u8>,
+    /// Raw bytes written right after the data, see `descriptor`
+    pub trailer: Vec<u8>,
+    /// Write zeros into local header's CRC and sizes, like streaming producers do
+    pub zero_local_sizes: bool,
+}
+
+impl Entry {
+    /// Stored entry with valid CRC and sizes
+    pub fn stored(name: &str, data: &[u8]) -> Self {
+        Self {
+            name: name.as_bytes().to_vec(),
+            data: data.to_vec(),
+            method: 0,
+            crc: crc32(data),
+            decompressed_size: data.len() as u32,
+            version_made_by: 20,
+            version_needed: 20,
+            ..Self::default()
+        }
+    }
+
+    /// Deflated entry with valid CRC and sizes
+    pub fn deflated(name: &str, data: &[u8]) -> Self {
+        Self {
+            data: miniz_oxide::deflate::compress_to_vec(data, 6),
+            method: 8,
+            ..Self::stored(name, data)
+        }
+    }
+
+    /// Zstandard-compressed entry with valid CRC and sizes
+    pub fn zstd(name: &str, data: &[u8]) -> Self {
+        use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+        Self {
+            data: compress_to_vec(data, CompressionLevel::Fastest),
+            method: 93,
+            ..Self::stored(name, data)
+        }
+    }
+
+    /// Marks entry as streamed and appends a data descriptor after the data
+    pub fn streamed(mut self, signature: bool, zip64: bool) -> Self {
+        self.flags |= 1 << 3;
+        self.zero_local_sizes = true;
+        self.trailer = descriptor(self.crc, self.data.len() as u64, self.decompressed_size as u64, signature, zip64);
+        self
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Archive {
+    pub entries: Vec<Entry>,
+    pub comment: Vec<u8>,
+    /// Write Zip64 end of central directory record and locator,
+    /// and saturate the counts and offsets in the end record
+    pub zip64: bool,
+}
+
+pub struct Built {
+    pub bytes: Vec<u8>,
+    pub local_offsets: Vec<usize>,
+    pub central_offsets: Vec<usize>,
+    pub central_dir_offset: usize,
+    pub eocd_offset: usize,
+}
+
+impl Archive {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        Self { entries, ..Self::default() }
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        self.build_detailed().bytes
+    }
+
+    pub fn build_detailed(&self) -> Built {
+        let mut out = Vec::new();
+        let mut local_offsets = Vec::new();
+
+        for e in &self.entries {
+            local_offsets.push(out.len());
+            let (crc, csize, dsize) = if e.zero_local_sizes {
+                (0, 0, 0)
+            } else {
+                (e.crc, e.data.len() as u32, e.decompressed_size)
+            };
+            put32(&mut out, 0x04034b50);
+            put16(&mut out, e.version_needed);
+            put16(&mut out, e.flags);
+            put16(&mut out, e.method);
+            put16(&mut out, e.last_mod_time);
+            put16(&mut out, e.last_mod_date);
+            put32(&mut out, crc);
+            put32(&mut out, csize);
+            put32(&mut out, dsize);
+            put16(&mut out, e.name.len() as u16);
+            put16(&mut out, e.local_extra.len() as u16);
+            out.extend_from_slice(&e.name);
+            out.extend_from_slice(&e.local_extra);
+            out.extend_from_slice(&e.data);
+            out.extend_from_slice(&e.trailer);
+        }
+
+        let central_dir_offset = out.len();
+        let mut central_offsets = Vec::new();
+        for (e, &local_offset) in self.entries.iter().zip(&local_offsets) {
+            central_offsets.push(out.len());
+            put32(&mut out, 0x02014b50);
+            put16(&mut out, e.version_made_by);
+            put16(&mut out, e.version_needed);
+            put16(&mut out, e.flags);
+            put16(&mut out, e.method);
+            put16(&mut out, e.last_mod_time);
+            put16(&mut out, e.last_mod_date);
+            put32(&mut out, e.crc);
+            put32(&mut out, e.data.len() as u32);
+            put32(&mut out, e.decompressed_size);
+            put16(&mut out, e.name.len() as u16);
+            put16(&mut out, e.central_extra.len() as u16);
+            put16(&mut out, e.comment.len() as u16);
+            put16(&mut out, 0);
+            put16(&mut out, e.internal_attr);
+            put32(&mut out, e.external_attr);
+            put32(&mut out, local_offset as u32);
+            out.extend_from_slice(&e.name);
+            out.extend_from_slice(&e.central_extra);
+            out.extend_from_slice(&e.comment);
+        }
+        let central_dir_size = out.len() - central_dir_offset;
+
+        let (count, size, offset) = if self.zip64 {
+            let zip64_eocd_offset = out.len();
+            put32(&mut out, 0x06064b50);
+            put64(&mut out, 44);
+            put16(&mut out, 45);
+            put16(&mut out, 45);
+            put32(&mut out, 0);
+            put32(&mut out, 0);
+            put64(&mut out, self.entries.len() as u64);
+            put64(&mut out, self.entries.len() as u64);
+            put64(&mut out, central_dir_size as u64);
+            put64(&mut out, central_dir_offset as u64);
+
+            put32(&mut out, 0x07064b50);
+            put32(&mut out, 0);
+            put64(&mut out, zip64_eocd_offset as u64);
+            put32(&mut out, 1);
+
+            (u16::MAX, u32::MAX, u32::MAX)
+        } else {
+            (self.entries.len() as u16, central_dir_size as u32, central_dir_offset as u32)
+        };
+
+        let eocd_offset = out.len();
+        put32(&mut out, 0x06054b50);
+        put16(&mut out, 0);
+        put16(&mut out, 0);
+        put16(&mut out, count);
+        put16(&mut out, count);
+        put32(&mut out, size);
+        put32(&mut out, offset);
+        put16(&mut out, self.comment.len() as u16);
+        out.extend_from_slice(&self.comment);
+
+        Built { bytes: out, local_offsets, central_offsets, central_dir_offset, eocd_offset }
+    }
+}
+
+impl Built {
+    /// Overwrites a 32-bit field of the `index`-th central directory header
+    pub fn patch_central32(&mut self, index: usize, field_offset: usize, value: u32) {
+        let at = self.central_offsets[index] + field_offset;
+        self.bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Overwrites a 16-bit field of the `index`-th central directory header
+    pub fn patch_central16(&mut self, index: usize, field_offset: usize, value: u16) {
+        let at = self.central_offsets[index] + field_offset;
+        self.bytes[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+pub fn descriptor(crc: u32, compressed_size: u64, decompressed_size: u64, signature: bool, zip64: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    if signature {
+        put32(&mut out, 0x08074b50);
+    }
+    put32(&mut out, crc);
+    if zip64 {
+        put64(&mut out, compressed_size);
+        put64(&mut out, decompressed_size);
+    } else {
+        put32(&mut out, compressed_size as u32);
+        put32(&mut out, decompressed_size as u32);
+    }
+    out
+}
+
+/// Single extra field with its header
+pub fn extra_field(signature: u16, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    put16(&mut out, signature);
+    put16(&mut out, data.len() as u16);
+    out.extend_from_slice(data);
+    out
+}
+
+pub fn put16(out: &mut Vec<u8>, x: u16) {
+    out.extend_from_slice(&x.to_le_bytes());
+}
+
+pub fn put32(out: &mut Vec<u8>, x: u32) {
+    out.extend_from_slice(&x.to_le_bytes());
+}
+
+pub fn put64(out: &mut Vec<u8>, x: u64) {
+    out.extend_from_slice(&x.to_le_bytes());
+}
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
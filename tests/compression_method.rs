@@ -0,0 +1,71 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::CompressionMethod;
+
+#[test]
+fn legacy_methods() {
+    assert!(matches!(CompressionMethod::from_u16(1), Some(CompressionMethod::Shrink)));
+    assert!(matches!(CompressionMethod::from_u16(2), Some(CompressionMethod::Reduce1)));
+    assert!(matches!(CompressionMethod::from_u16(3), Some(CompressionMethod::Reduce2)));
+    assert!(matches!(CompressionMethod::from_u16(4), Some(CompressionMethod::Reduce3)));
+    assert!(matches!(CompressionMethod::from_u16(5), Some(CompressionMethod::Reduce4)));
+    assert!(matches!(CompressionMethod::from_u16(6), Some(CompressionMethod::Implode)));
+    assert!(CompressionMethod::from_u16(7).is_none());
+}
+
+#[test]
+fn legacy_archive_iterates() {
+    let entries = (1..=6)
+        .map(|method| Entry { method, ..Entry::stored(&format!("{method}"), b"old") })
+        .collect();
+    let bytes = Archive::new(entries).build();
+
+    let methods: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter
+        .map(|file| file.compression_method as u16)
+        .collect();
+    assert_eq!(methods, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn modern_methods() {
+    assert!(matches!(CompressionMethod::from_u16(9), Some(CompressionMethod::Deflate64)));
+    assert!(matches!(CompressionMethod::from_u16(12), Some(CompressionMethod::Bzip2)));
+    assert!(matches!(CompressionMethod::from_u16(14), Some(CompressionMethod::Lzma)));
+    assert!(matches!(CompressionMethod::from_u16(95), Some(CompressionMethod::Xz)));
+    assert!(matches!(CompressionMethod::from_u16(98), Some(CompressionMethod::Ppmd1)));
+    assert!(matches!(CompressionMethod::from_u16(99), Some(CompressionMethod::Aes)));
+    assert!(CompressionMethod::from_u16(100).is_none());
+}
+
+#[test]
+fn unknown_method_is_skipped() {
+    let bytes = Archive::new(vec![
+        Entry { method: 93, ..Entry::stored("zstd", b"z") },
+        Entry { method: 7, ..Entry::stored("unknown", b"?") },
+        Entry::stored("plain", b"p"),
+    ]).build();
+
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files.len(), 2);
+    assert!(matches!(files[0].compression_method, CompressionMethod::Zstd));
+    assert_eq!(files[1].filename, b"plain");
+}
+
+#[test]
+fn entries_with_method() {
+    let bytes = Archive::new(vec![
+        Entry::deflated("a.txt", b"aaaaaaaa"),
+        Entry::stored("b.txt", b"b"),
+        Entry { method: 7, ..Entry::stored("unknown", b"?") },
+        Entry { method: 93, ..Entry::stored("zstd", b"z") },
+        Entry::deflated("c.txt", b"cccccccc"),
+    ]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    let deflated: Vec<_> = zip.entries_with_method(CompressionMethod::Deflate).map(|file| file.filename).collect();
+    assert_eq!(deflated, [b"a.txt", b"c.txt"]);
+    let stored: Vec<_> = zip.entries_with_method(CompressionMethod::Plain).map(|file| file.filename).collect();
+    assert_eq!(stored, [b"b.txt"]);
+    assert_eq!(zip.entries_with_method(CompressionMethod::Bzip2).count(), 0);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::crc::{self, CrcPoly};
+
+#[test]
+fn check_values() {
+    assert_eq!(crc::crc32(b"123456789"), 0xCBF43926);
+    assert_eq!(crc::crc32c(b"123456789"), 0xE3069283);
+    assert_eq!(crc::crc32(b""), 0);
+    assert_eq!(crc::crc32c(b""), 0);
+}
+
+#[test]
+fn verify_crc_with() {
+    let mut castagnoli = Entry::stored("c", b"123456789");
+    castagnoli.crc = 0xE3069283;
+    let bytes = Archive::new(vec![Entry::stored("i", b"123456789"), castagnoli]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+
+    assert!(files[0].verify_crc_with(b"123456789", CrcPoly::IsoHdlc));
+    assert!(!files[0].verify_crc_with(b"123456789", CrcPoly::Castagnoli));
+    assert!(files[1].verify_crc_with(b"123456789", CrcPoly::Castagnoli));
+    assert!(!files[1].verify_crc_with(b"123456789", CrcPoly::IsoHdlc));
+}
+
+#[test]
+fn verify_crc() {
+    assert_eq!(piz::File::crc_of(b"The quick brown fox jumps over the lazy dog"), 0x414FA339);
+    assert_eq!(piz::File::crc_of(&[0; 32]), 0x190A55AD);
+
+    let bytes = Archive::new(vec![Entry::stored("a", b"hello, world")]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    let stored = file.bytes().unwrap();
+    assert!(file.verify_crc(stored));
+    assert!(!file.verify_crc(b"hello, world!"));
+}
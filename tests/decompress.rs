@@ -0,0 +1,288 @@
+#![cfg(feature = "decompress")]
+
+mod common;
+
+use common::{Archive, Entry};
+use piz::decompress::DecompressError;
+use piz::CompressionMethod;
+
+const TEXT: &[u8] = b"It was the best of times, it was the worst of times, \
+    it was the age of wisdom, it was the age of foolishness...";
+
+#[test]
+fn round_trip() {
+    let bytes = Archive::new(vec![
+        Entry::deflated("deflated.txt", TEXT),
+        Entry::stored("stored.txt", TEXT),
+        Entry::deflated("empty", b""),
+    ]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+
+    assert!(files[0].bytes().unwrap().len() < TEXT.len());
+    assert_eq!(files[0].decompress().unwrap(), TEXT);
+    assert_eq!(files[1].decompress().unwrap(), TEXT);
+    assert_eq!(files[2].decompress().unwrap(), b"");
+}
+
+#[test]
+fn errors() {
+    let mut wrong_crc = Entry::deflated("crc", TEXT);
+    wrong_crc.crc ^= 1;
+    let mut too_small = Entry::deflated("small", TEXT);
+    too_small.decompressed_size -= 1;
+    let mut too_big = Entry::deflated("big", TEXT);
+    too_big.decompressed_size += 1;
+    let mut corrupt = Entry::deflated("corrupt", TEXT);
+    corrupt.data = vec![0xFF; 10];
+    let mut truncated = Entry::deflated("truncated", TEXT);
+    truncated.data.truncate(10);
+    let imploded = Entry { method: 6, ..Entry::stored("imploded", TEXT) };
+
+    let bytes = Archive::new(vec![wrong_crc, too_small, too_big, corrupt, truncated, imploded]).build();
+    let errors: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter
+        .map(|file| file.decompress().unwrap_err())
+        .collect();
+
+    let crc = common::crc32(TEXT);
+    let len = TEXT.len() as u64;
+    assert_eq!(errors, [
+        DecompressError::CrcMismatch { expected: crc ^ 1, actual: crc },
+        DecompressError::SizeMismatch { expected: len - 1 },
+        DecompressError::SizeMismatch { expected: len + 1 },
+        DecompressError::Corrupt,
+        DecompressError::Corrupt,
+        DecompressError::UnsupportedMethod(CompressionMethod::Implode),
+    ]);
+}
+
+#[test]
+fn lying_sizes() {
+    let mut claims_4gib = Entry::deflated("4gib", TEXT);
+    claims_4gib.decompressed_size = u32::MAX;
+    // Zip64 extra with just the decompressed size, the only saturated field
+    let mut claims_1tib = Entry::deflated("1tib", TEXT);
+    claims_1tib.decompressed_size = u32::MAX;
+    claims_1tib.central_extra = common::extra_field(0x0001, &(1u64 << 40).to_le_bytes());
+    // Large enough for the output to grow a couple of times
+    let large = TEXT.repeat(5000);
+
+    let bytes = Archive::new(vec![claims_4gib, claims_1tib, Entry::deflated("large", &large)]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files[0].decompress(), Err(DecompressError::SizeMismatch { expected: u32::MAX.into() }));
+    assert_eq!(files[1].decompress(), Err(DecompressError::SizeMismatch { expected: 1 << 40 }));
+    assert_eq!(files[2].decompress().unwrap(), large);
+}
+
+#[test]
+fn strong_encryption() {
+    let strong = Entry { flags: 1 | 1 << 6, ..Entry::deflated("strong", TEXT) };
+    let bytes = Archive::new(vec![strong]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    assert!(file.is_strong_encrypted());
+    assert_eq!(file.decompress(), Err(DecompressError::Encrypted));
+}
+
+#[test]
+fn validate_decompressed() {
+    let mut wrong_crc = Entry::deflated("crc", TEXT);
+    wrong_crc.crc ^= 1;
+    let unsupported = Entry { method: 6, ..Entry::stored("imploded", TEXT) };
+    let encrypted = Entry { flags: 1, ..Entry::stored("encrypted", TEXT) };
+    let bytes = Archive::new(vec![Entry::deflated("good", TEXT), wrong_crc, unsupported, encrypted]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    assert_eq!(zip.validate(), Ok(()));
+    let crc = common::crc32(TEXT);
+    assert_eq!(zip.validate_decompressed(), Err(vec![piz::ValidationError::Content {
+        filename: b"crc",
+        error: DecompressError::CrcMismatch { expected: crc ^ 1, actual: crc },
+    }]));
+}
+
+/// `TEXT` compressed with Python's `bz2.compress`
+#[cfg(feature = "bzip2")]
+const TEXT_BZ2: &[u8] = &[
+    0x42, 0x5a, 0x68, 0x39, 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, 0xd0, 0xe1, 0x60, 0x14, 0x00, 0x00,
+    0x0e, 0x95, 0x80, 0x40, 0x05, 0x00, 0x20, 0x37, 0xe7, 0x9c, 0x80, 0x20, 0x00, 0x54, 0x25, 0x4d,
+    0x3d, 0x49, 0xa3, 0x65, 0x34, 0x69, 0xe9, 0x04, 0x92, 0x9e, 0x91, 0xea, 0x68, 0x6d, 0x23, 0xd2,
+    0x42, 0xe5, 0xd6, 0xd4, 0x35, 0x05, 0x87, 0x06, 0x7c, 0xd9, 0x42, 0x83, 0x40, 0xcf, 0x2a, 0x46,
+    0xe0, 0xfa, 0x68, 0x23, 0x26, 0x02, 0xec, 0x3c, 0xf1, 0x4b, 0x92, 0x42, 0x05, 0x3a, 0xe5, 0x38,
+    0x68, 0x8c, 0x15, 0xdb, 0x21, 0x1b, 0x67, 0x0a, 0x7e, 0x2e, 0xe4, 0x8a, 0x70, 0xa1, 0x21, 0xa1,
+    0xc2, 0xc0, 0x28,
+];
+
+#[cfg(feature = "bzip2")]
+#[test]
+fn bzip2() {
+    let ok = Entry { method: 12, data: TEXT_BZ2.to_vec(), ..Entry::stored("ok", TEXT) };
+    let mut too_small = Entry { name: b"small".to_vec(), ..ok.clone() };
+    too_small.decompressed_size -= 1;
+    let mut too_big = Entry { name: b"big".to_vec(), ..ok.clone() };
+    too_big.decompressed_size += 1;
+    let truncated = Entry { name: b"truncated".to_vec(), data: TEXT_BZ2[..60].to_vec(), ..ok.clone() };
+    let empty = Entry { name: b"empty".to_vec(), data: Vec::new(), ..ok.clone() };
+
+    let bytes = Archive::new(vec![ok, too_small, too_big, truncated, empty]).build();
+    let results: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|file| file.decompress()).collect();
+
+    let len = TEXT.len() as u64;
+    assert_eq!(results[0].as_deref(), Ok(TEXT));
+    assert_eq!(results[1], Err(DecompressError::SizeMismatch { expected: len - 1 }));
+    assert_eq!(results[2], Err(DecompressError::SizeMismatch { expected: len + 1 }));
+    assert!(results[3].is_err());
+    assert!(results[4].is_err());
+}
+
+/// Deflate64 stream with a fixed Huffman block: `hello`, a 40000 byte match
+/// (length code 285 with 16 extra bits), ` world!` and then a 12 byte match
+/// 40009 bytes back (distance code 30), see `deflate64_expected`
+#[cfg(feature = "deflate64")]
+const DEFLATE64: &[u8] = &[
+    0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x1f, 0xed, 0xe1, 0x24, 0x14, 0xca, 0xf3, 0x8b, 0x72, 0x52, 0x14,
+    0x91, 0x1f, 0x12, 0x07, 0x00,
+];
+
+#[cfg(feature = "deflate64")]
+fn deflate64_expected() -> Vec<u8> {
+    let mut out = b"hello".repeat(8001);
+    out.extend_from_slice(b" world!");
+    out.extend_from_slice(b"lohellohello");
+    out
+}
+
+#[cfg(feature = "deflate64")]
+#[test]
+fn deflate64() {
+    let expected = deflate64_expected();
+    let ok = Entry { method: 9, data: DEFLATE64.to_vec(), ..Entry::stored("ok", &expected) };
+    let mut too_small = Entry { name: b"small".to_vec(), ..ok.clone() };
+    too_small.decompressed_size -= 1;
+    let mut too_big = Entry { name: b"big".to_vec(), ..ok.clone() };
+    too_big.decompressed_size += 1;
+    let truncated = Entry { name: b"truncated".to_vec(), data: DEFLATE64[..10].to_vec(), ..ok.clone() };
+    // Plain Deflate decoders can't read it
+    assert!(miniz_oxide::inflate::decompress_to_vec(DEFLATE64).is_err());
+
+    let bytes = Archive::new(vec![ok, too_small, too_big, truncated]).build();
+    let results: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|file| file.decompress()).collect();
+
+    let len = expected.len() as u64;
+    assert_eq!(results[0].as_deref(), Ok(&expected[..]));
+    assert_eq!(results[1], Err(DecompressError::SizeMismatch { expected: len - 1 }));
+    assert_eq!(results[2], Err(DecompressError::SizeMismatch { expected: len + 1 }));
+    assert!(results[3].is_err());
+}
+
+/// `TEXT` compressed with liblzma, with the ZIP LZMA header and an end marker
+#[cfg(feature = "lzma")]
+const TEXT_LZMA: &[u8] = &[
+    0x09, 0x14, 0x05, 0x00, 0x5d, 0x00, 0x00, 0x80, 0x00, 0x00, 0x24, 0x9d, 0x00, 0x07, 0x73, 0x43,
+    0x85, 0x19, 0xc1, 0xbc, 0x61, 0x9e, 0x16, 0x43, 0x3f, 0x49, 0xa5, 0x27, 0x15, 0xb4, 0xf5, 0x10,
+    0x18, 0x54, 0xbf, 0xcd, 0x91, 0x38, 0x3b, 0x33, 0x8b, 0x52, 0x7d, 0x4a, 0xe7, 0x72, 0xd2, 0xb1,
+    0xd8, 0x4a, 0x2a, 0x51, 0x00, 0x44, 0xb2, 0xe1, 0x69, 0x9f, 0x12, 0x41, 0xf5, 0x50, 0xc0, 0x17,
+    0xd2, 0xcd, 0xfc, 0x5f, 0x05, 0x7d, 0x78, 0xbe, 0x54, 0x01, 0xff, 0xf5, 0x85, 0xe0, 0x00,
+];
+
+/// `TEXT` compressed with liblzma into an XZ stream
+#[cfg(feature = "xz")]
+const TEXT_XZ: &[u8] = &[
+    0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00, 0x00, 0x04, 0xe6, 0xd6, 0xb4, 0x46, 0x02, 0x00, 0x21, 0x01,
+    0x16, 0x00, 0x00, 0x00, 0x74, 0x2f, 0xe5, 0xa3, 0xe0, 0x00, 0x6e, 0x00, 0x40, 0x5d, 0x00, 0x24,
+    0x9d, 0x00, 0x07, 0x73, 0x43, 0x85, 0x19, 0xc1, 0xbc, 0x61, 0x9e, 0x16, 0x43, 0x3f, 0x49, 0xa5,
+    0x27, 0x15, 0xb4, 0xf5, 0x10, 0x18, 0x54, 0xbf, 0xcd, 0x91, 0x38, 0x3b, 0x33, 0x8b, 0x52, 0x7d,
+    0x4a, 0xe7, 0x72, 0xd2, 0xb1, 0xd8, 0x4a, 0x2a, 0x51, 0x00, 0x44, 0xb2, 0xe1, 0x69, 0x9f, 0x12,
+    0x41, 0xf5, 0x50, 0xc0, 0x17, 0xd2, 0xcd, 0xfc, 0x5f, 0x05, 0x7d, 0x5d, 0xfa, 0x31, 0x80, 0x00,
+    0xa1, 0x5a, 0xef, 0xd1, 0xbe, 0x26, 0x82, 0x51, 0x00, 0x01, 0x5c, 0x6f, 0xba, 0xdb, 0x85, 0xeb,
+    0x1f, 0xb6, 0xf3, 0x7d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x59, 0x5a,
+];
+
+#[cfg(feature = "lzma")]
+#[test]
+fn lzma() {
+    let ok = Entry { method: 14, flags: 1 << 1, data: TEXT_LZMA.to_vec(), ..Entry::stored("ok", TEXT) };
+    // Same stream, but decoded up to the declared size without looking for the marker
+    let sized = Entry { name: b"sized".to_vec(), flags: 0, ..ok.clone() };
+    let mut too_small = Entry { name: b"small".to_vec(), ..ok.clone() };
+    too_small.decompressed_size -= 1;
+    let mut too_big = Entry { name: b"big".to_vec(), ..ok.clone() };
+    too_big.decompressed_size += 1;
+    let mut bad_header = Entry { name: b"header".to_vec(), ..ok.clone() };
+    bad_header.data[2] = 4;
+
+    let bytes = Archive::new(vec![ok, sized, too_small, too_big, bad_header]).build();
+    let results: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|file| file.decompress()).collect();
+
+    let len = TEXT.len() as u64;
+    assert_eq!(results[0].as_deref(), Ok(TEXT));
+    assert_eq!(results[1].as_deref(), Ok(TEXT));
+    assert_eq!(results[2], Err(DecompressError::SizeMismatch { expected: len - 1 }));
+    assert!(results[3].is_err());
+    assert_eq!(results[4], Err(DecompressError::Corrupt));
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn xz() {
+    let ok = Entry { method: 95, data: TEXT_XZ.to_vec(), ..Entry::stored("ok", TEXT) };
+    let mut too_small = Entry { name: b"small".to_vec(), ..ok.clone() };
+    too_small.decompressed_size -= 1;
+    let truncated = Entry { name: b"truncated".to_vec(), data: TEXT_XZ[..40].to_vec(), ..ok.clone() };
+
+    let bytes = Archive::new(vec![ok, too_small, truncated]).build();
+    let results: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|file| file.decompress()).collect();
+
+    assert_eq!(results[0].as_deref(), Ok(TEXT));
+    assert_eq!(results[1], Err(DecompressError::SizeMismatch { expected: TEXT.len() as u64 - 1 }));
+    assert!(results[2].is_err());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd() {
+    let mut too_small = Entry::zstd("small", TEXT);
+    too_small.decompressed_size -= 1;
+    let mut corrupt = Entry::zstd("corrupt", TEXT);
+    corrupt.data.truncate(10);
+
+    let bytes = Archive::new(vec![Entry::zstd("zstd.txt", TEXT), too_small, corrupt]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files[0].decompress().unwrap(), TEXT);
+    assert_eq!(files[1].decompress(), Err(DecompressError::SizeMismatch { expected: TEXT.len() as u64 - 1 }));
+    assert_eq!(files[2].decompress(), Err(DecompressError::Corrupt));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn seekable_deflated() {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let bytes = Archive::new(vec![Entry::deflated("d", TEXT)]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    let mut reader = file.seekable_reader().unwrap();
+    reader.seek(SeekFrom::Start(7)).unwrap();
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"the best");
+}
+
+#[test]
+fn extract_iter() {
+    let encrypted = Entry { flags: 1, ..Entry::stored("secret", TEXT) };
+    let bytes = Archive::new(vec![
+        Entry::deflated("first.txt", TEXT),
+        Entry::deflated("second.txt", b"second"),
+        encrypted,
+    ]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    let mut results = zip.extract_iter();
+    let (file, data) = results.next().unwrap().unwrap();
+    assert_eq!((file.filename, &data[..]), (&b"first.txt"[..], TEXT));
+    let (file, data) = results.next().unwrap().unwrap();
+    assert_eq!((file.filename, &data[..]), (&b"second.txt"[..], &b"second"[..]));
+    assert_eq!(results.next().unwrap().err(), Some(DecompressError::Encrypted));
+    assert!(results.next().is_none());
+
+    // The stored iterator is still there
+    assert_eq!(zip.central_dir_iter.count(), 3);
+}
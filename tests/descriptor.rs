@@ -0,0 +1,131 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::descriptor::DataDescriptor;
+
+fn zip64_extra(decompressed_size: u64, compressed_size: u64, offset: u64) -> Vec<u8> {
+    let mut data = Vec::new();
+    common::put64(&mut data, decompressed_size);
+    common::put64(&mut data, compressed_size);
+    common::put64(&mut data, offset);
+    common::put32(&mut data, 0);
+    common::extra_field(0x0001, &data)
+}
+
+#[test]
+fn parse_widths() {
+    let short = common::descriptor(0xdeadbeef, 5, 7, false, false);
+    let d = DataDescriptor::parse(&short, false).unwrap();
+    assert_eq!((d.crc, d.compressed_size, d.decompressed_size, d.len), (0xdeadbeef, 5, 7, 12));
+
+    let signed = common::descriptor(0xdeadbeef, 5, 7, true, false);
+    assert_eq!(DataDescriptor::parse(&signed, false).unwrap().len, 16);
+
+    let zip64 = common::descriptor(0xdeadbeef, 5 << 32, 7 << 32, true, true);
+    let d = DataDescriptor::parse(&zip64, true).unwrap();
+    assert_eq!((d.compressed_size, d.decompressed_size, d.len), (5 << 32, 7 << 32, 24));
+
+    assert!(DataDescriptor::parse(&signed[..10], false).is_none());
+}
+
+#[test]
+fn zip64_streaming_archive() {
+    let mut first = Entry::stored("first.txt", b"streamed zip64 data").streamed(true, true);
+    first.version_needed = 45;
+    let mut second = Entry::stored("second.txt", b"hello").streamed(true, true);
+    // Local header, name, data and descriptor of the first entry
+    second.central_extra = zip64_extra(5, 5, 30 + 9 + 19 + 24);
+    let bytes = Archive::new(vec![first, second]).build();
+
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files.len(), 2);
+
+    // Version needed 4.5
+    assert!(files[0].is_zip64());
+    let d = files[0].data_descriptor().unwrap();
+    assert_eq!(d.compressed_size, 19);
+    assert_eq!(d.decompressed_size, 19);
+    assert_eq!(d.crc, files[0].decompressed_crc);
+    assert_eq!(d.len, 24);
+
+    // Zip64 extra field
+    assert!(files[1].is_zip64());
+    assert_eq!(files[1].bytes().unwrap(), b"hello");
+    assert_eq!(files[1].data_descriptor().unwrap().len, 24);
+}
+
+#[test]
+fn no_descriptor_without_flag() {
+    let bytes = Archive::new(vec![Entry::stored("a", b"abc")]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.bytes().unwrap(), b"abc");
+    assert!(!file.is_zip64());
+    assert!(file.data_descriptor().is_none());
+    assert!(!file.has_data_descriptor());
+}
+
+#[test]
+fn zeroed_local_sizes() {
+    let bytes = Archive::new(vec![
+        Entry::stored("a", b"streamed").streamed(false, false),
+        Entry::stored("b", b"next"),
+    ]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+
+    assert!(files[0].has_data_descriptor());
+    assert_eq!(files[0].bytes().unwrap(), b"streamed");
+    let descriptor = files[0].data_descriptor().unwrap();
+    assert_eq!((descriptor.compressed_size, descriptor.decompressed_size), (8, 8));
+    assert_eq!(files[0].check_data_descriptor(), Ok(()));
+    assert_eq!(files[1].bytes().unwrap(), b"next");
+}
+
+#[test]
+fn crc_cross_check() {
+    let good = Entry::stored("good", b"data").streamed(true, false);
+    let mut bad = Entry::stored("bad", b"data").streamed(true, false);
+    bad.trailer = common::descriptor(!bad.crc, 4, 4, true, false);
+    let plain = Entry::stored("plain", b"data");
+    let bytes = Archive::new(vec![good, bad, plain]).build();
+
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files[0].check_data_descriptor(), Ok(()));
+    assert_eq!(
+        files[1].check_data_descriptor(),
+        Err(piz::MismatchError::CrcFieldMismatch {
+            central: files[1].decompressed_crc,
+            other: !files[1].decompressed_crc,
+        })
+    );
+    assert_eq!(files[2].check_data_descriptor(), Ok(()));
+
+    // Local header validation defers to the descriptor for streamed entries
+    assert_eq!(files[0].validate_local_header(), Ok(()));
+    assert_eq!(files[1].validate_local_header(), files[1].check_data_descriptor());
+    #[cfg(feature = "alloc")]
+    assert_eq!(piz::Zip::new(&bytes).unwrap().validate(), Err(vec![piz::ValidationError::LocalHeader {
+        filename: b"bad",
+        error: piz::MismatchError::CrcFieldMismatch { central: files[1].decompressed_crc, other: !files[1].decompressed_crc },
+    }]));
+}
+
+#[test]
+fn data_descriptor_size() {
+    let entries = [(false, false), (true, false), (false, true), (true, true)]
+        .into_iter()
+        .map(|(signature, zip64)| {
+            let mut entry = Entry::stored("x", b"data").streamed(signature, zip64);
+            if zip64 {
+                entry.version_needed = 45;
+            }
+            entry
+        })
+        .chain([Entry::stored("plain", b"data")])
+        .collect();
+    let bytes = Archive::new(entries).build();
+
+    let sizes: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter
+        .map(|file| file.data_descriptor_size())
+        .collect();
+    assert_eq!(sizes, [12, 16, 20, 24, 0]);
+}
@@ -0,0 +1,274 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::extra::{AesExtra, ExtendedTimestamp, Extra, NtfsTimestamps, UnicodeComment, UnicodePath, UnixOld, UnixUidGid, Zip64};
+
+#[test]
+fn zip64_without_disk_number() {
+    let mut data = Vec::new();
+    common::put64(&mut data, 1);
+    common::put64(&mut data, 2);
+    common::put64(&mut data, 3);
+
+    let zip64 = Zip64::parse(&data).unwrap();
+    assert_eq!((zip64.decompressed_size, zip64.compressed_size, zip64.local_header_record_offset), (1, 2, 3));
+    assert_eq!(zip64.disk_no, 0);
+
+    common::put32(&mut data, 4);
+    assert_eq!(Zip64::parse(&data).unwrap().disk_no, 4);
+
+    assert!(Zip64::parse(&data[..20]).is_none());
+}
+
+#[test]
+fn zip64_only_saturated_fields() {
+    let mut entry = Entry::stored("big", b"0123456789");
+    // Both sizes saturated, offset is not
+    let mut data = Vec::new();
+    common::put64(&mut data, 10);
+    common::put64(&mut data, 10);
+    entry.central_extra = common::extra_field(Zip64::SIGNATURE, &data);
+    let mut built = Archive::new(vec![entry]).build_detailed();
+    built.patch_central32(0, 20, u32::MAX);
+    built.patch_central32(0, 24, u32::MAX);
+
+    let file = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.next().unwrap();
+    assert!(file.is_zip64());
+    assert_eq!(file.compressed_size_u64(), 10);
+    assert_eq!(file.uncompressed_size_u64(), 10);
+    assert_eq!(file.bytes().unwrap(), b"0123456789");
+}
+
+#[test]
+fn extra_fields_accessors() {
+    let mut data = Vec::new();
+    common::put64(&mut data, 10);
+    common::put64(&mut data, 7);
+    common::put64(&mut data, 0);
+    let mut entry = Entry::stored("big", b"");
+    entry.central_extra = [common::extra_field(0xcafe, b"vendor"), common::extra_field(Zip64::SIGNATURE, &data)].concat();
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+
+    let signatures: Vec<_> = file.extra_fields_iter().map(|(signature, _)| signature).collect();
+    assert_eq!(signatures, [0xcafe, Zip64::SIGNATURE]);
+
+    let fields = piz::ExtraFields(file.extra_fields);
+    assert_eq!(fields.get(Zip64::SIGNATURE), Some(&data[..]));
+    assert_eq!(fields.get(0xcafe), Some(&b"vendor"[..]));
+    assert_eq!(fields.get(0x7075), None);
+    let zip64 = fields.parse::<Zip64>().unwrap();
+    assert_eq!((zip64.decompressed_size, zip64.compressed_size), (10, 7));
+    assert_eq!(fields.into_iter().count(), 2);
+}
+
+#[test]
+fn reserved_fields_are_opaque() {
+    let fields: &[(u16, &[u8])] = &[
+        (0x0007, b"av info"),
+        (0x0008, b""),
+        (Zip64::SIGNATURE, b""),
+        (0x0009, b"\x01\x02\x03"),
+        (0x000C, b""),
+        (0x0065, b"\xff"),
+        (0x4690, b"vendor"),
+    ];
+    let mut entry = Entry::stored("dense", b"x");
+    entry.central_extra = fields.iter().flat_map(|(signature, data)| common::extra_field(*signature, data)).collect();
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+
+    let all: Vec<_> = piz::extra::Iter { data: file.extra_fields }.collect();
+    assert_eq!(all, fields);
+
+    let unknown: Vec<_> = file.unknown_extras().collect();
+    let expected: Vec<_> = fields.iter().copied().filter(|(signature, _)| *signature != Zip64::SIGNATURE).collect();
+    assert_eq!(unknown, expected);
+    assert_eq!(file.bytes().unwrap(), b"x");
+}
+
+#[test]
+fn malformed_lengths() {
+    use piz::extra::{Iter, MalformedExtra};
+
+    let mut data = common::extra_field(0xcafe, b"ok");
+    common::put16(&mut data, 0xbeef);
+    common::put16(&mut data, 10);
+    data.extend_from_slice(b"short");
+
+    let mut iter = Iter { data: &data };
+    assert_eq!(iter.try_next(), Ok(Some((0xcafe, &b"ok"[..]))));
+    assert_eq!(iter.try_next(), Err(MalformedExtra::LengthExceedsData { signature: 0xbeef, declared: 10, available: 5 }));
+    assert_eq!(iter.try_next(), Ok(None));
+    // The lenient one just ends
+    assert_eq!(Iter { data: &data }.count(), 1);
+
+    let mut iter = Iter { data: b"\x01\x00\x00" };
+    assert_eq!(iter.try_next(), Err(MalformedExtra::TruncatedHeader { len: 3 }));
+    assert_eq!(Iter { data: &[] }.try_next(), Ok(None));
+}
+
+#[test]
+fn extended_timestamp() {
+    let mut local = vec![0b111];
+    common::put32(&mut local, 1_600_000_000);
+    common::put32(&mut local, 1_600_000_001);
+    common::put32(&mut local, -1i32 as u32);
+    // Central directory version keeps the flags but has only the modification time
+    let central = local[..5].to_vec();
+
+    let mut entry = Entry::stored("t", b"");
+    entry.local_extra = common::extra_field(ExtendedTimestamp::SIGNATURE, &local);
+    entry.central_extra = common::extra_field(ExtendedTimestamp::SIGNATURE, &central);
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+
+    let timestamp = file.extra::<ExtendedTimestamp>().unwrap();
+    assert_eq!(timestamp.flags, 0b111);
+    assert_eq!((timestamp.modified, timestamp.accessed, timestamp.created), (Some(1_600_000_000), None, None));
+    assert_eq!(file.unknown_extras().count(), 0);
+
+    let timestamp: ExtendedTimestamp = piz::extra::find(file.local_extra_fields().unwrap()).unwrap();
+    assert_eq!(timestamp.accessed, Some(1_600_000_001));
+    assert_eq!(timestamp.created, Some(-1));
+
+    assert!(ExtendedTimestamp::parse(&[]).is_none());
+    assert_eq!(ExtendedTimestamp::parse(&[0]).unwrap().modified, None);
+}
+
+#[test]
+fn ntfs_timestamps() {
+    // Reserved, then tag 1 with 24 bytes of times, 2020-09-13 12:26:40 UTC
+    let blob = [
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x18, 0x00,
+        0x00, 0x80, 0xa6, 0x21, 0xc9, 0x89, 0xd6, 0x01,
+        0x80, 0x16, 0x3f, 0x22, 0xc9, 0x89, 0xd6, 0x01,
+        0x00, 0x80, 0xa6, 0x21, 0xc9, 0x89, 0xd6, 0x01,
+    ];
+    let times = NtfsTimestamps::parse(&blob).unwrap();
+    assert_eq!(times.modified, times.created);
+    assert_eq!(times.accessed - times.modified, 10_000_000);
+    assert_eq!(NtfsTimestamps::to_unix(times.modified), 1_600_000_000);
+    assert_eq!(NtfsTimestamps::to_unix(times.accessed), 1_600_000_001);
+    assert_eq!(NtfsTimestamps::to_unix(0), -11_644_473_600);
+
+    let mut entry = Entry::stored("n", b"");
+    entry.central_extra = common::extra_field(NtfsTimestamps::SIGNATURE, &blob);
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.extra::<NtfsTimestamps>(), Some(times));
+
+    // Times attribute missing or truncated
+    assert!(NtfsTimestamps::parse(&blob[..4]).is_none());
+    assert!(NtfsTimestamps::parse(&blob[..20]).is_none());
+}
+
+#[test]
+fn unix_uid_gid() {
+    let blob = [1, 4, 0xe8, 0x03, 0x00, 0x00, 4, 0x64, 0x00, 0x00, 0x00];
+    let mut entry = Entry::stored("u", b"");
+    entry.central_extra = common::extra_field(UnixUidGid::SIGNATURE, &blob);
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.extra::<UnixUidGid>(), Some(UnixUidGid { version: 1, uid: 1000, gid: 100 }));
+
+    // Other widths
+    assert_eq!(UnixUidGid::parse(&[1, 2, 0x01, 0x02, 1, 0x03]).map(|x| (x.uid, x.gid)), Some((0x0201, 3)));
+    assert_eq!(UnixUidGid::parse(&[1, 0, 0]).map(|x| (x.uid, x.gid)), Some((0, 0)));
+
+    // Too wide or truncated
+    assert!(UnixUidGid::parse(&[1, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]).is_none());
+    assert!(UnixUidGid::parse(&blob[..8]).is_none());
+}
+
+#[test]
+fn unix_old() {
+    let mut local = Vec::new();
+    common::put32(&mut local, 1_600_000_001);
+    common::put32(&mut local, 1_600_000_000);
+    common::put16(&mut local, 1000);
+    common::put16(&mut local, 100);
+    let central = local[..8].to_vec();
+
+    let mut entry = Entry::stored("u", b"");
+    entry.local_extra = common::extra_field(UnixOld::SIGNATURE, &local);
+    entry.central_extra = common::extra_field(UnixOld::SIGNATURE, &central);
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+
+    let times = UnixOld { accessed: 1_600_000_001, modified: 1_600_000_000, uid: None, gid: None };
+    assert_eq!(file.extra::<UnixOld>(), Some(times));
+    let owned = UnixOld { uid: Some(1000), gid: Some(100), ..times };
+    assert_eq!(piz::extra::find(file.local_extra_fields().unwrap()), Some(owned));
+    assert_eq!(file.unknown_extras().count(), 0);
+
+    assert!(UnixOld::parse(&local[..7]).is_none());
+}
+
+#[test]
+fn unicode_path() {
+    let legacy_name = b"m?sli.txt";
+    let mut field = vec![1];
+    common::put32(&mut field, common::crc32(legacy_name));
+    field.extend_from_slice("myśli.txt".as_bytes());
+
+    let path = UnicodePath::parse(&field).unwrap();
+    assert_eq!(path.version, 1);
+    assert_eq!(path.name, "myśli.txt".as_bytes());
+    assert!(path.matches(legacy_name));
+
+    let mut entry = Entry::stored("m?sli.txt", b"");
+    entry.central_extra = common::extra_field(UnicodePath::SIGNATURE, &field);
+    // Renamed by a tool that didn't update the field
+    let mut renamed = entry.clone();
+    renamed.name = b"other.txt".to_vec();
+    let bytes = Archive::new(vec![entry, renamed, Entry::stored("plain", b"")]).build();
+    let names: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.preferred_filename()).collect();
+    assert_eq!(names, ["myśli.txt".as_bytes(), b"other.txt", b"plain"]);
+
+    assert!(UnicodePath::parse(&field[..3]).is_none());
+}
+
+#[test]
+fn unicode_comment() {
+    let legacy_comment = b"?????? ???";
+    let mut field = vec![1];
+    common::put32(&mut field, common::crc32(legacy_comment));
+    field.extend_from_slice("Привет мир".as_bytes());
+
+    let comment = UnicodeComment::parse(&field).unwrap();
+    assert_eq!(comment.version, 1);
+    assert_eq!(comment.comment, "Привет мир".as_bytes());
+    assert!(comment.matches(legacy_comment));
+
+    let mut entry = Entry::stored("hello.txt", b"");
+    entry.comment = legacy_comment.to_vec();
+    entry.central_extra = common::extra_field(UnicodeComment::SIGNATURE, &field);
+    // Comment edited by a tool that didn't update the field
+    let mut edited = entry.clone();
+    edited.comment = b"edited".to_vec();
+    let bytes = Archive::new(vec![entry, edited, Entry::stored("plain", b"")]).build();
+    let comments: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.preferred_comment()).collect();
+    assert_eq!(comments, ["Привет мир".as_bytes(), b"edited", b""]);
+
+    assert!(UnicodeComment::parse(&field[..4]).is_none());
+}
+
+#[test]
+fn aes() {
+    // AE-2, 256-bit key, deflated
+    let blob = [0x02, 0x00, b'A', b'E', 0x03, 0x08, 0x00];
+    let mut entry = Entry { method: 99, ..Entry::stored("secret", b"ciphertext") };
+    entry.central_extra = common::extra_field(AesExtra::SIGNATURE, &blob);
+    let bytes = Archive::new(vec![entry, Entry::stored("plain", b"")]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+
+    assert!(files[0].is_aes_encrypted());
+    assert!(!files[1].is_aes_encrypted());
+    let aes = files[0].extra::<AesExtra>().unwrap();
+    assert_eq!(aes, AesExtra { version: 2, vendor: *b"AE", strength: 3, compression_method: 8 });
+    assert_eq!(aes.key_bits(), Some(256));
+    assert_eq!(aes.actual_compression_method(), Some(piz::CompressionMethod::Deflate));
+
+    assert!(AesExtra::parse(&blob[..6]).is_none());
+}
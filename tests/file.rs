@@ -0,0 +1,242 @@
+mod common;
+
+use common::{Archive, Entry};
+
+#[test]
+fn sizes_u64() {
+    let mut huge = Entry::stored("huge", b"compressed");
+    let mut zip64 = Vec::new();
+    common::put64(&mut zip64, 5 << 30);
+    huge.central_extra = common::extra_field(0x0001, &zip64);
+    let mut built = Archive::new(vec![huge]).build_detailed();
+    // Only decompressed size is saturated, so only it is in the extra field
+    built.patch_central32(0, 24, u32::MAX);
+
+    let file = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.uncompressed_size_u64(), 5 << 30);
+    assert_eq!(file.compressed_size_u64(), 10);
+    assert_eq!(file.bytes().unwrap(), b"compressed");
+}
+
+#[test]
+fn nested_zip() {
+    let inner = Archive::new(vec![
+        Entry::stored("x", b"xxx"),
+        Entry::stored("y", b"yyy"),
+    ]).build();
+    let mut compressed = Entry::stored("inner.zip", &inner);
+    compressed.method = 8;
+    let outer = Archive::new(vec![Entry::stored("inner.zip", &inner), compressed]).build();
+
+    let files: Vec<_> = piz::Zip::new(&outer).unwrap().central_dir_iter.collect();
+    let nested = files[0].as_zip().unwrap();
+    let names: Vec<_> = nested.central_dir_iter.map(|file| file.filename).collect();
+    assert_eq!(names, [b"x", b"y"]);
+    assert!(files[1].as_zip().is_none());
+}
+
+#[test]
+fn unix_mode() {
+    let unix = Entry {
+        version_made_by: 3 << 8 | 30,
+        external_attr: 0o100644 << 16,
+        ..Entry::stored("unix", b"")
+    };
+    let dos = Entry { external_attr: 0x20, ..Entry::stored("dos", b"") };
+    let bytes = Archive::new(vec![unix, dos]).build();
+
+    let modes: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.unix_mode()).collect();
+    assert_eq!(modes, [Some(0o100644), None]);
+}
+
+#[test]
+fn version_needed() {
+    let zip64 = Entry { version_needed: 45, ..Entry::stored("big", b"") };
+    let bytes = Archive::new(vec![zip64, Entry::stored("small", b"")]).build();
+
+    let versions: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.version_needed()).collect();
+    assert_eq!(versions, [45, 20]);
+}
+
+#[test]
+fn raw_header() {
+    let entry = Entry {
+        version_made_by: 3 << 8 | 63,
+        internal_attr: 1,
+        flags: 1 << 11,
+        ..Entry::stored("text.txt", b"")
+    };
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+
+    let header = file.raw_header();
+    assert_eq!(header.version_made_by.get(), 3 << 8 | 63);
+    assert_eq!(header.file_attr_internal.get(), 1);
+    assert_eq!(header.flags.get(), 1 << 11);
+    assert_eq!(header.disk_number.get(), 0);
+}
+
+#[test]
+fn local_header() {
+    let mut entry = Entry::stored("dir/file.txt", b"data").streamed(true, false);
+    entry.local_extra = common::extra_field(0xcafe, b"local");
+    let built = Archive::new(vec![Entry::stored("a", b""), entry]).build_detailed();
+    let file = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.nth(1).unwrap();
+    assert_eq!(file.local_file_offset(), built.local_offsets[1]);
+
+    let local = file.local_header().unwrap();
+    assert_eq!(local.filename_len.get() as usize, file.filename.len());
+    assert_eq!(local.extra_field_len.get(), 9);
+    assert_eq!(local.uncompressed_crc.get(), 0);
+
+    // Pointing past the end of the archive
+    let mut built = Archive::new(vec![Entry::stored("a", b"")]).build_detailed();
+    built.patch_central32(0, 42, 100_000);
+    assert!(piz::Zip::new(&built.bytes).unwrap().central_dir_iter.next().unwrap().local_header().is_none());
+}
+
+#[test]
+fn empty_stored_file() {
+    let built = Archive::new(vec![Entry::stored("empty", b"")]).build_detailed();
+    let file = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.decompressed_size, 0);
+    assert_eq!(file.bytes(), Some(&b""[..]));
+    assert_eq!(file.data_range(), Some(built.central_dir_offset..built.central_dir_offset));
+
+    // Data ending exactly at the end of the buffer
+    let local = &built.bytes[..built.central_dir_offset];
+    let file = piz::local::LocalHeaderIter::new(local).next().unwrap();
+    assert_eq!(file.bytes(), Some(&b""[..]));
+}
+
+#[test]
+fn compression_ratio() {
+    let text = b"abcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabc";
+    let deflated = Entry { data: vec![0; 15], method: 8, ..Entry::stored("deflated", text) };
+    let bytes = Archive::new(vec![
+        Entry::stored("stored", text),
+        deflated,
+        Entry::stored("empty", b""),
+    ]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+
+    assert_eq!(files[0].compression_ratio(), Some(1.0));
+    assert_eq!(files[1].compressed_size_u64(), 15);
+    assert_eq!(files[1].compression_ratio(), Some(0.25));
+    assert_eq!(files[2].compression_ratio(), None);
+}
+
+#[test]
+fn is_dir() {
+    let unix_dir = Entry {
+        version_made_by: 3 << 8 | 30,
+        external_attr: 0o040755 << 16,
+        ..Entry::stored("bin", b"")
+    };
+    let bytes = Archive::new(vec![
+        Entry::stored("dir/", b""),
+        unix_dir,
+        Entry::stored("dir/file", b"data"),
+    ]).build();
+
+    let dirs: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.is_dir()).collect();
+    assert_eq!(dirs, [true, true, false]);
+}
+
+#[test]
+fn is_text() {
+    let text = Entry { internal_attr: 1, ..Entry::stored("readme.txt", b"hello\r\n") };
+    // Other bits don't matter
+    let binary = Entry { internal_attr: 2, ..Entry::stored("data.bin", b"\0\x01") };
+    let bytes = Archive::new(vec![text, binary]).build();
+    let texts: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.is_text()).collect();
+    assert_eq!(texts, [true, false]);
+}
+
+#[test]
+fn is_symlink() {
+    let link = Entry {
+        version_made_by: 3 << 8 | 30,
+        external_attr: 0o120777 << 16,
+        ..Entry::stored("link", b"target/file")
+    };
+    let regular = Entry {
+        version_made_by: 3 << 8 | 30,
+        external_attr: 0o100644 << 16,
+        ..Entry::stored("file", b"")
+    };
+    // Same bits, but not from Unix
+    let dos = Entry { external_attr: 0o120777 << 16, ..Entry::stored("dos", b"") };
+    let bytes = Archive::new(vec![link, regular, dos]).build();
+
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    let links: Vec<_> = files.iter().map(|f| f.is_symlink()).collect();
+    assert_eq!(links, [true, false, false]);
+    assert!(!files[0].is_dir());
+    assert_eq!(files[0].bytes().unwrap(), b"target/file");
+}
+
+#[test]
+fn encryption_flags() {
+    let bytes = Archive::new(vec![
+        Entry::stored("plain", b""),
+        Entry { flags: 1, ..Entry::stored("traditional", b"") },
+        Entry { flags: 1 | 1 << 6, ..Entry::stored("strong", b"") },
+    ]).build();
+
+    let flags: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter
+        .map(|f| (f.is_encrypted(), f.is_strong_encrypted()))
+        .collect();
+    assert_eq!(flags, [(false, false), (true, false), (true, true)]);
+}
+
+#[test]
+fn general_purpose_flags() {
+    use piz::{DeflateLevel, GeneralPurposeFlags};
+
+    // Encrypted, fast deflate, data descriptor, UTF-8
+    let flags = GeneralPurposeFlags(1 | 2 << 1 | 1 << 3 | 1 << 11);
+    assert!(flags.is_encrypted());
+    assert!(flags.has_data_descriptor());
+    assert!(flags.is_utf8());
+    assert!(!flags.is_strong_encrypted());
+    assert_eq!(flags.deflate_level(), DeflateLevel::Fast);
+    assert_eq!(GeneralPurposeFlags(0b110).deflate_level(), DeflateLevel::SuperFast);
+    // Strong encryption bit alone means nothing
+    assert!(!GeneralPurposeFlags(1 << 6).is_strong_encrypted());
+
+    let bytes = Archive::new(vec![Entry { flags: flags.0, ..Entry::stored("x", b"") }]).build();
+    assert_eq!(piz::Zip::new(&bytes).unwrap().get(0).unwrap().flags(), flags);
+}
+
+#[test]
+fn validate_local_header() {
+    use piz::MismatchError;
+
+    let built = Archive::new(vec![
+        Entry::stored("a", b"aaa"),
+        Entry::stored("b", b"bbb").streamed(true, false),
+    ]).build_detailed();
+    let validate = |bytes: &[u8], index: usize| {
+        piz::Zip::new(bytes).unwrap().get(index).unwrap().validate_local_header()
+    };
+    assert_eq!(validate(&built.bytes, 0), Ok(()));
+    // Zeroed sizes and CRC in a streamed entry are fine
+    assert_eq!(validate(&built.bytes, 1), Ok(()));
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.local_offsets[0] + 8..][..2].copy_from_slice(&8u16.to_le_bytes());
+    assert_eq!(validate(&bytes, 0), Err(MismatchError::CompressionMethodMismatch { central: 0, local: 8 }));
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.local_offsets[0]] = 0;
+    assert_eq!(validate(&bytes, 0), Err(MismatchError::InvalidLocalSignature { found: 0x04034b00 }));
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.local_offsets[0] + 30] = b'x';
+    assert_eq!(validate(&bytes, 0), Err(MismatchError::FilenameMismatch));
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.local_offsets[0] + 18..][..4].copy_from_slice(&2u32.to_le_bytes());
+    assert_eq!(validate(&bytes, 0), Err(MismatchError::CompressedSizeMismatch { central: 3, local: Some(2) }));
+}
@@ -0,0 +1,54 @@
+//! Inputs that used to panic, or that are close to the arithmetic edges
+//! the fuzz target in `fuzz/` goes after
+
+mod common;
+
+use common::{Archive, Entry};
+
+/// Zip64 archive whose Zip64 end record says the directory is at `offset`
+fn zip64_with_offset(marker: &[u8], offset: u64) -> Vec<u8> {
+    let built = Archive { zip64: true, ..Archive::new(vec![Entry::stored("a", b"a")]) }.build_detailed();
+    let mut bytes = [marker, &built.bytes].concat();
+    let record = marker.len() + built.eocd_offset - 20 - 56;
+    bytes[record + 48..][..8].copy_from_slice(&offset.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn central_dir_offset_near_max() {
+    for offset in [u64::MAX, u64::MAX - 3, 1 << 63] {
+        let bytes = zip64_with_offset(b"", offset);
+        assert!(matches!(piz::Zip::parse(&bytes), Err(piz::ZipError::TruncatedDirectory { .. })));
+
+        let options = piz::OpenOptions { allow_prefix: false, ..Default::default() };
+        let bytes = zip64_with_offset(b"PK00", offset);
+        assert!(piz::Zip::parse_with_options(&bytes, options).is_err());
+    }
+}
+
+#[test]
+fn garbage_around_end_record() {
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    let end_record = &built.bytes[built.eocd_offset..];
+
+    // End record alone, and preceded by bytes that look like other records
+    for prefix in [&b""[..], b"PK\x01\x02", b"PK\x06\x07\xff\xff\xff\xff", b"PK\x03\x04PK\x01\x02"] {
+        let bytes = [prefix, end_record].concat();
+        if let Some(zip) = piz::Zip::new(&bytes) {
+            for file in zip.files() {
+                let _ = file.bytes();
+            }
+            for _ in zip.strict_iter() {}
+        }
+    }
+
+    // Every truncation of a valid archive
+    for len in 0..built.bytes.len() {
+        if let Some(zip) = piz::Zip::new(&built.bytes[..len]) {
+            for file in zip.files() {
+                let _ = file.bytes();
+            }
+        }
+        for _ in piz::local::LocalHeaderIter::new(&built.bytes[..len]) {}
+    }
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use common::{Archive, Entry};
+use piz::index::IndexedZip;
+
+#[test]
+fn get_index() {
+    let entries = (0..10).map(|i| Entry::stored(&format!("file{i}"), format!("data{i}").as_bytes())).collect();
+    let bytes = Archive::new(entries).build();
+
+    let index = IndexedZip::new(piz::Zip::new(&bytes).unwrap());
+    assert_eq!(index.len(), 10);
+
+    for (i, file) in piz::Zip::new(&bytes).unwrap().central_dir_iter.enumerate() {
+        let indexed = index.get_index(i).unwrap();
+        assert_eq!(indexed.filename, file.filename);
+        assert_eq!(indexed.bytes(), file.bytes());
+    }
+    assert_eq!(index.get_index(7).unwrap().filename, b"file7");
+    assert!(index.get_index(10).is_none());
+}
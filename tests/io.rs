@@ -0,0 +1,124 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+use common::{Archive, Entry};
+
+#[test]
+fn seekable_stored() {
+    let bytes = Archive::new(vec![Entry::stored("db", b"0123456789abcdef")]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    let mut reader = file.seekable_reader().unwrap();
+
+    let mut buf = [0; 4];
+    reader.seek(SeekFrom::Start(10)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abcd");
+
+    reader.seek(SeekFrom::End(-3)).unwrap();
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"def");
+
+    assert_eq!(reader.seek(SeekFrom::Current(-16)).unwrap(), 0);
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"0123");
+}
+
+#[test]
+fn seekable_unsupported() {
+    let entry = Entry { method: 6, ..Entry::stored("old", b"not really imploded") };
+    let bytes = Archive::new(vec![entry]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.seekable_reader().err().unwrap().kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn reader_stored() {
+    let bytes = Archive::new(vec![Entry::stored("s", b"stored data")]).build();
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    let mut out = Vec::new();
+    std::io::copy(&mut file.reader().unwrap(), &mut out).unwrap();
+    assert_eq!(out, b"stored data");
+}
+
+#[cfg(feature = "decompress")]
+#[test]
+fn reader_deflated_in_chunks() {
+    let text: Vec<u8> = (0..20_000u32).flat_map(|i| format!("line {}\n", i * 7919 % 1000).into_bytes()).collect();
+    let mut truncated = Entry::deflated("truncated", &text);
+    truncated.data.truncate(truncated.data.len() / 2);
+    let bytes = Archive::new(vec![Entry::deflated("d", &text), truncated]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+
+    let mut reader = files[0].reader().unwrap();
+    let mut out = Vec::new();
+    let mut chunk = [0; 7];
+    loop {
+        let n = reader.read(&mut chunk).unwrap();
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&chunk[..n]);
+    }
+    assert_eq!(out, text);
+
+    let err = files[1].reader().unwrap().read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn zip_reader() {
+    let mut entry = Entry::stored("b.txt", b"bbb");
+    entry.local_extra = common::extra_field(0xcafe, b"local only");
+    let archive = Archive::new(vec![Entry::stored("a.txt", b"aaaa"), entry, Entry::deflated("c.txt", b"cccccccc")]);
+    let zip64 = Archive { zip64: true, ..archive.clone() }.build();
+    let prefixed = [&b"stub"[..], &archive.build()].concat();
+
+    for bytes in [archive.build(), zip64, prefixed] {
+        let zip = piz::Zip::new(&bytes).unwrap();
+        let mut reader = piz::io::ZipReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.prefix_len(), zip.prefix_len() as u64);
+
+        let expected: Vec<_> = zip.files().map(|file| (file.filename, file.decompressed_crc)).collect();
+        let found: Vec<_> = reader.files().map(|file| (file.filename, file.decompressed_crc)).collect();
+        assert_eq!(found, expected);
+        let offsets: Vec<_> = reader.files().map(|file| file.central_record_offset()).collect();
+        assert_eq!(offsets, zip.files().map(|file| file.central_record_offset()).collect::<Vec<_>>());
+        assert!(reader.files().all(|file| file.bytes().is_none()));
+
+        for (index, file) in zip.files().enumerate() {
+            let mut data = Vec::new();
+            reader.entry_reader(index).unwrap().read_to_end(&mut data).unwrap();
+            assert_eq!(data, file.bytes().unwrap());
+        }
+        assert_eq!(reader.entry_reader(3).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    // Indices count records, including ones `files` skips
+    let unknown = Entry { method: 0x1234, ..Entry::stored("unknown", b"???") };
+    let bytes = Archive::new(vec![unknown, Entry::stored("a.txt", b"aaaa")]).build();
+    let mut reader = piz::io::ZipReader::new(std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(reader.entry_reader(0).unwrap_err().kind(), ErrorKind::NotFound);
+    let mut data = Vec::new();
+    reader.entry_reader(1).unwrap().read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"aaaa");
+
+    let err = piz::io::ZipReader::new(std::io::Cursor::new(vec![0; 100])).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.into_inner().unwrap().downcast::<piz::ZipError>().ok().map(|err| *err), Some(piz::ZipError::EndRecordNotFound));
+}
+
+#[test]
+fn encrypted_is_refused() {
+    let traditional = Entry { flags: 1, ..Entry::stored("zipcrypto", b"ciphertext") };
+    let strong = Entry { flags: 1 | 1 << 6, ..Entry::stored("strong", b"ciphertext") };
+    let bytes = Archive::new(vec![traditional, strong]).build();
+
+    for file in piz::Zip::new(&bytes).unwrap().central_dir_iter {
+        assert_eq!(file.reader().err().map(|err| err.kind()), Some(ErrorKind::Unsupported));
+        assert_eq!(file.seekable_reader().err().map(|err| err.kind()), Some(ErrorKind::Unsupported));
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::local::LocalHeaderIter;
+
+#[test]
+fn truncated_central_directory() {
+    let built = Archive::new(vec![
+        Entry::stored("a.txt", b"first"),
+        Entry::stored("b.txt", b"second"),
+    ]).build_detailed();
+    // Cut in the middle of the central directory
+    let bytes = &built.bytes[..built.central_dir_offset + 10];
+    assert!(piz::Zip::new(bytes).is_none());
+
+    let files: Vec<_> = LocalHeaderIter::new(bytes).collect();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].filename, b"a.txt");
+    assert_eq!(files[0].bytes().unwrap(), b"first");
+    assert_eq!(files[1].filename, b"b.txt");
+    assert_eq!(files[1].bytes().unwrap(), b"second");
+    assert!(files[1].verify_crc(files[1].bytes().unwrap()));
+}
+
+#[test]
+fn stops_after_streamed_entry() {
+    let bytes = Archive::new(vec![
+        Entry::stored("a", b"plain"),
+        Entry::stored("b", b"streamed").streamed(true, false),
+        Entry::stored("c", b"unreachable"),
+    ]).build();
+
+    let names: Vec<_> = LocalHeaderIter::new(&bytes).map(|file| file.filename).collect();
+    assert_eq!(names, [&b"a"[..], b"b"]);
+}
+
+#[test]
+fn recover_streamed_entries() {
+    let mut built = Archive::new(vec![
+        Entry::stored("a", b"plain"),
+        Entry::deflated("b", b"streamed streamed streamed").streamed(true, false),
+        Entry::stored("c", b"after PK\x07\x08 lookalike").streamed(true, false),
+        Entry::stored("d", b"last"),
+    ]).build_detailed();
+    built.bytes[built.central_dir_offset..].fill(0);
+    assert!(piz::Zip::new(&built.bytes).is_none());
+
+    let files: Vec<_> = piz::Zip::recover(&built.bytes).collect();
+    let names: Vec<_> = files.iter().map(|file| file.filename).collect();
+    assert_eq!(names, [&b"a"[..], b"b", b"c", b"d"]);
+    assert_eq!(files[1].decompressed_size, 26);
+    assert_eq!(files[1].decompressed_crc, common::crc32(b"streamed streamed streamed"));
+    assert_eq!(files[2].bytes().unwrap(), b"after PK\x07\x08 lookalike");
+    assert_eq!(files[3].bytes().unwrap(), b"last");
+
+    // Plain local header walk still gives up after the first streamed entry
+    assert_eq!(LocalHeaderIter::new(&built.bytes).count(), 2);
+}
+
+#[test]
+fn recover_mismatched_descriptor() {
+    let mut lying = Entry::stored("b", b"sized in the header");
+    lying.flags |= 1 << 3;
+    lying.trailer = common::descriptor(lying.crc, 4, 4, true, false);
+    let bytes = Archive::new(vec![
+        Entry::stored("a", b"plain"),
+        lying,
+        Entry::stored("c", b"unreachable"),
+    ]).build();
+
+    let files: Vec<_> = piz::Zip::recover(&bytes).collect();
+    let names: Vec<_> = files.iter().map(|file| file.filename).collect();
+    assert_eq!(names, [&b"a"[..], b"b"]);
+    // Sizes come from the local header, not the descriptor
+    assert_eq!(files[1].bytes().unwrap(), b"sized in the header");
+}
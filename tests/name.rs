@@ -0,0 +1,73 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::NameEncoding;
+
+fn archive() -> Vec<u8> {
+    let mut utf8 = Entry::stored("", b"");
+    utf8.name = "zażółć".as_bytes().to_vec();
+    utf8.flags = 1 << 11;
+    let mut legacy = Entry::stored("", b"");
+    legacy.name = b"m\x81sli".to_vec();
+    Archive::new(vec![utf8, legacy]).build()
+}
+
+#[test]
+fn name_encoding_policy() {
+    let bytes = archive();
+
+    let auto: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.name_is_utf8()).collect();
+    assert_eq!(auto, [true, false]);
+
+    let utf8: Vec<_> = piz::Zip::open_with(&bytes, NameEncoding::Utf8).unwrap().central_dir_iter.map(|f| f.name_is_utf8()).collect();
+    assert_eq!(utf8, [true, true]);
+
+    let cp437: Vec<_> = piz::Zip::open_with(&bytes, NameEncoding::Cp437).unwrap().central_dir_iter.map(|f| f.name_is_utf8()).collect();
+    assert_eq!(cp437, [false, false]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn decoded_name() {
+    let bytes = archive();
+
+    let auto: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.decoded_name().into_owned()).collect();
+    assert_eq!(auto, ["zażółć", "müsli"]);
+
+    let utf8: Vec<_> = piz::Zip::open_with(&bytes, NameEncoding::Utf8).unwrap().central_dir_iter.map(|f| f.decoded_name().into_owned()).collect();
+    assert_eq!(utf8, ["zażółć", "m\u{FFFD}sli"]);
+
+    let cp437: Vec<_> = piz::Zip::open_with(&bytes, NameEncoding::Cp437).unwrap().central_dir_iter.map(|f| f.decoded_name().into_owned()).collect();
+    assert_eq!(cp437, ["za┼╝├│┼é─ç", "müsli"]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn filename_lossy() {
+    let bytes = archive();
+
+    let auto: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.filename_lossy().into_owned()).collect();
+    assert_eq!(auto, ["zażółć", "müsli"]);
+
+    // Not UTF-8 after all, so it falls back to CP437
+    let utf8: Vec<_> = piz::Zip::open_with(&bytes, NameEncoding::Utf8).unwrap().central_dir_iter.map(|f| f.filename_lossy().into_owned()).collect();
+    assert_eq!(utf8, ["zażółć", "müsli"]);
+
+    let file = piz::Zip::new(&bytes).unwrap().central_dir_iter.next().unwrap();
+    assert!(matches!(file.filename_lossy(), std::borrow::Cow::Borrowed("zażółć")));
+}
+
+#[test]
+fn filename_str() {
+    let bytes = archive();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert!(files[0].filename_is_utf8());
+    assert!(!files[1].filename_is_utf8());
+    assert_eq!(files[0].filename_str(), Some("zażółć"));
+    assert_eq!(files[1].filename_str(), None);
+
+    // Forcing UTF-8 doesn't change the flag, and invalid names are still rejected
+    let files: Vec<_> = piz::Zip::open_with(&bytes, NameEncoding::Utf8).unwrap().central_dir_iter.collect();
+    assert!(!files[1].filename_is_utf8());
+    assert_eq!(files[1].filename_str(), None);
+}
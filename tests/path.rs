@@ -0,0 +1,50 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::path::sanitize;
+
+#[test]
+fn sanitize_names() {
+    for name in ["a.txt", "dir/", "dir/sub/file", "./a", "a..b/..c", "a//b"] {
+        assert_eq!(sanitize(name.as_bytes()), Some(name), "{name}");
+    }
+
+    let malicious: &[&[u8]] = &[
+        b"../x",
+        b"..\\..\\x",
+        b"/abs",
+        b"a/../../b",
+        b"a/..",
+        b"..",
+        b"C:/Windows/x",
+        b"c:x",
+        b"\\\\server\\share\\x",
+        b"a\0.txt",
+        b"",
+        b"m\x81sli",
+    ];
+    for name in malicious {
+        assert_eq!(sanitize(name), None, "{}", name.escape_ascii());
+    }
+}
+
+#[test]
+fn safe_path() {
+    let bytes = Archive::new(vec![
+        Entry::stored("ok/file", b""),
+        Entry::stored("../../etc/passwd", b""),
+    ]).build();
+    let paths: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.safe_path()).collect();
+    assert_eq!(paths, [Some("ok/file"), None]);
+}
+
+#[test]
+fn path_components() {
+    let bytes = Archive::new(vec![
+        Entry::stored("a/b/c.txt", b""),
+        Entry::stored("dir//sub/", b""),
+    ]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+    let components: Vec<Vec<&[u8]>> = zip.central_dir_iter.map(|f| f.path_components().collect()).collect();
+    assert_eq!(components, [vec![&b"a"[..], b"b", b"c.txt"], vec![&b"dir"[..], b"sub"]]);
+}
@@ -0,0 +1,52 @@
+//! Fields are read as little-endian regardless of the host, so these should
+//! pass unchanged on big-endian targets too, e.g. with
+//! `cross test --target s390x-unknown-linux-gnu`.
+
+mod common;
+
+use common::{Archive, Entry};
+use piz::raw::{CentralDirectoryRecordEnd, Le16, Le32};
+
+#[test]
+fn le_fields() {
+    assert_eq!(Le16([0x34, 0x12]).get(), 0x1234);
+    assert_eq!(Le32([0x50, 0x4b, 0x05, 0x06]).get(), piz::raw::CENTRAL_DIR_END_SIGNATURE);
+
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    let (end, comment_len) = CentralDirectoryRecordEnd::find(&built.bytes).unwrap();
+    assert_eq!(comment_len, 0);
+    assert_eq!(end.central_dir_records_total.get(), 1);
+    assert_eq!(end.central_dir_offset.get() as usize, built.central_dir_offset);
+}
+
+#[test]
+fn byte_swapped_archive() {
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    let file = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.decompressed_size, 3);
+
+    // An archive written in the wrong byte order is not an archive at all
+    let mut swapped = built.bytes.clone();
+    swapped[built.eocd_offset..][..4].reverse();
+    assert!(piz::Zip::new(&swapped).is_none());
+
+    // Swapped sizes are read as swapped, not in the host's order
+    let mut swapped = built.bytes.clone();
+    swapped[built.central_offsets[0] + 24..][..4].reverse();
+    let file = piz::Zip::new(&swapped).unwrap().central_dir_iter.next().unwrap();
+    assert_eq!(file.decompressed_size, 0x0300_0000);
+}
+
+#[test]
+fn truncated_header() {
+    use piz::raw::{from_prefix, CentralDirectoryFileHeader};
+
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    let record = &built.bytes[built.central_offsets[0]..built.eocd_offset];
+    let header: &CentralDirectoryFileHeader = from_prefix(record).unwrap();
+    assert_eq!(header.signature.get(), piz::raw::CENTRAL_DIR_HEADER_SIGNATURE);
+
+    assert!(from_prefix::<CentralDirectoryFileHeader>(&record[..45]).is_none());
+    #[cfg(feature = "bytemuck")]
+    assert!(bytemuck::try_from_bytes::<CentralDirectoryFileHeader>(&record[..45]).is_err());
+}
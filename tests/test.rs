@@ -1,5 +1,3 @@
-use piz;
-
 #[test]
 fn test() {
     let bytes = std::fs::read("/tmp/zip.zip").unwrap();
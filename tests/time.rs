@@ -0,0 +1,47 @@
+mod common;
+
+use common::{Archive, Entry};
+use piz::time::DosDateTime;
+
+#[test]
+fn from_dos() {
+    let t = DosDateTime::from_dos(0x2C21, 0x4A5D).unwrap();
+    assert_eq!(t, DosDateTime { year: 2002, month: 1, day: 1, hour: 9, minute: 18, second: 58 });
+    assert!(t.is_valid());
+
+    let t = DosDateTime::from_dos(0x5A8F, 0xBF7D).unwrap();
+    assert_eq!(t, DosDateTime { year: 2025, month: 4, day: 15, hour: 23, minute: 59, second: 58 });
+
+    assert_eq!(DosDateTime::from_dos(0, 0x4A5D), None);
+    assert!(!DosDateTime::from_dos(0x0001, 0).unwrap().is_valid());
+}
+
+#[test]
+fn modified() {
+    let entry = Entry { last_mod_date: 0x2C21, last_mod_time: 0x4A5D, ..Entry::stored("a", b"") };
+    let bytes = Archive::new(vec![entry, Entry::stored("b", b"")]).build();
+    let files: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files[0].modified().map(|t| t.year), Some(2002));
+    assert_eq!(files[1].modified(), None);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn modified_datetime() {
+    use piz::extra::{ExtendedTimestamp, Extra};
+
+    let dos = Entry { last_mod_date: 0x5A8F, last_mod_time: 0xBF7D, ..Entry::stored("dos", b"") };
+    let mut unix = Entry { last_mod_date: 0x2C21, ..Entry::stored("unix", b"") };
+    let mut mtime = vec![1];
+    common::put32(&mut mtime, 1_600_000_000);
+    unix.central_extra = common::extra_field(ExtendedTimestamp::SIGNATURE, &mtime);
+    let invalid = Entry { last_mod_date: 0x0001, ..Entry::stored("invalid", b"") };
+    let bytes = Archive::new(vec![dos, unix, invalid, Entry::stored("unset", b"")]).build();
+    let times: Vec<_> = piz::Zip::new(&bytes).unwrap().central_dir_iter.map(|f| f.modified_datetime()).collect();
+
+    let dos = times[0].unwrap();
+    assert_eq!((dos.year(), dos.month(), dos.day()), (2025, time::Month::April, 15));
+    assert_eq!((dos.hour(), dos.minute(), dos.second()), (23, 59, 58));
+    assert_eq!(times[1].unwrap().unix_timestamp(), 1_600_000_000);
+    assert_eq!(times[2..], [None, None]);
+}
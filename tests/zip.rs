@@ -0,0 +1,842 @@
+mod common;
+
+use common::{Archive, Entry};
+
+#[test]
+fn validate_links() {
+    let archive = Archive::new(vec![
+        Entry::stored("a.txt", b"aaa"),
+        Entry::stored("b.txt", b"bbb"),
+    ]);
+    let built = archive.build_detailed();
+    assert_eq!(piz::Zip::new(&built.bytes).unwrap().validate_links(), Ok(()));
+
+    // Break the signature of the second local header
+    let mut bytes = built.bytes.clone();
+    bytes[built.local_offsets[1]] = 0;
+    let err = piz::Zip::new(&bytes).unwrap().validate_links().unwrap_err();
+    assert_eq!(err.filename, b"b.txt");
+    assert_eq!(err.local_file_offset, built.local_offsets[1]);
+
+    // Point the first record past the end of the buffer
+    let mut bytes = built.bytes.clone();
+    let offset_field = built.central_offsets[0] + 42;
+    bytes[offset_field..offset_field + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = piz::Zip::new(&bytes).unwrap().validate_links().unwrap_err();
+    assert_eq!(err.filename, b"a.txt");
+    assert_eq!(err.local_file_offset, u32::MAX as usize);
+}
+
+#[test]
+fn open_full_scan() {
+    let mut bytes = Archive::new(vec![Entry::stored("a.txt", b"aaa")]).build();
+    assert!(piz::Zip::open_full_scan(&bytes).is_some());
+
+    // Trailer bigger than any comment could be
+    bytes.extend(std::iter::repeat_n(0xAA, 100_000));
+    assert!(piz::Zip::new(&bytes).is_none());
+
+    let zip = piz::Zip::open_full_scan(&bytes).unwrap();
+    let files: Vec<_> = zip.central_dir_iter.collect();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].filename, b"a.txt");
+}
+
+#[test]
+fn zip_mut_in_place() {
+    let secret: Vec<u8> = b"plain text".iter().map(|b| b ^ 0x5A).collect();
+    // Unknown method, so `files` skips it but indices still count it
+    let unknown = Entry { method: 0x1234, ..Entry::stored("unknown", b"???") };
+    let mut bytes = Archive::new(vec![
+        unknown,
+        Entry::stored("a.txt", b"aaa"),
+        Entry::stored("secret.txt", &secret),
+    ]).build();
+
+    let mut zip = piz::ZipMut::new(&mut bytes).unwrap();
+    let index = (0..3).find(|&i| zip.as_zip().get(i).is_some_and(|file| file.filename == b"secret.txt")).unwrap();
+    assert_eq!(index, 2);
+    zip.bytes_mut(index).unwrap().iter_mut().for_each(|b| *b ^= 0x5A);
+
+    let files: Vec<_> = zip.files().collect();
+    assert_eq!(files[0].bytes().unwrap(), b"aaa");
+    assert_eq!(files[1].bytes().unwrap(), b"plain text");
+    assert!(zip.bytes_mut(0).is_none());
+    assert!(zip.bytes_mut(3).is_none());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn diff() {
+    let old = Archive::new(vec![
+        Entry::stored("same", b"same"),
+        Entry::stored("changed", b"v1"),
+        Entry::stored("removed", b"bye"),
+    ]).build();
+    let new = Archive::new(vec![
+        Entry::stored("added", b"hi"),
+        Entry::stored("changed", b"v2"),
+        Entry::stored("same", b"same"),
+    ]).build();
+
+    let old = piz::Zip::new(&old).unwrap();
+    let new = piz::Zip::new(&new).unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, [b"added"]);
+    assert_eq!(diff.removed, [b"removed"]);
+    assert_eq!(diff.changed, [b"changed"]);
+
+    assert!(old.diff(&old).is_empty());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn diff_duplicate_names() {
+    let old = Archive::new(vec![
+        Entry::stored("dup", b"v1"),
+        Entry::stored("gone", b"1"),
+        Entry::stored("dup", b"v2"),
+        Entry::stored("gone", b"2"),
+    ]).build();
+    let new = Archive::new(vec![
+        Entry::stored("dup", b"v3"),
+        Entry::stored("new", b"1"),
+        Entry::stored("dup", b"v2"),
+        Entry::stored("new", b"2"),
+    ]).build();
+
+    let old = piz::Zip::new(&old).unwrap();
+    let new = piz::Zip::new(&new).unwrap();
+
+    // Only the last "dup" counts, and it's the same in both
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, [b"new"]);
+    assert_eq!(diff.removed, [b"gone"]);
+    assert!(diff.changed.is_empty());
+
+    let diff = new.diff(&old);
+    assert_eq!(diff.added, [b"gone"]);
+    assert_eq!(diff.removed, [b"new"]);
+}
+
+#[test]
+fn strict_iter_empty_filename() {
+    let built = Archive::new(vec![
+        Entry::stored("a.txt", b"aaa"),
+        Entry::stored("", b"nameless"),
+    ]).build_detailed();
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+
+    let strict: Vec<_> = zip.strict_iter().collect();
+    assert_eq!(strict.len(), 2);
+    assert_eq!(strict[0].as_ref().unwrap().filename, b"a.txt");
+    assert_eq!(
+        strict[1].as_ref().unwrap_err(),
+        &piz::StrictError::EmptyFilename { offset: built.central_offsets[1] },
+    );
+
+    let lenient: Vec<_> = zip.central_dir_iter.map(|file| file.filename).collect();
+    assert_eq!(lenient, [&b"a.txt"[..], b""]);
+}
+
+#[test]
+fn strict_iter_signatures_and_counts() {
+    use piz::StrictError;
+
+    let built = Archive::new(vec![
+        Entry::stored("a", b"aaa"),
+        Entry::stored("b", b"bbb"),
+    ]).build_detailed();
+    assert!(piz::Zip::new(&built.bytes).unwrap().strict_iter().all(|file| file.is_ok()));
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.central_offsets[1]] ^= 0xFF;
+    let errors: Vec<_> = piz::Zip::new(&bytes).unwrap().strict_iter().filter_map(Result::err).collect();
+    assert_eq!(errors, [StrictError::InvalidSignature { offset: built.central_offsets[1] }]);
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.local_offsets[0]] ^= 0xFF;
+    let errors: Vec<_> = piz::Zip::new(&bytes).unwrap().strict_iter().filter_map(Result::err).collect();
+    assert_eq!(errors, [StrictError::InvalidLocalSignature {
+        offset: built.central_offsets[0],
+        local_file_offset: built.local_offsets[0],
+    }]);
+
+    for (declared, found) in [(3u16, 2), (1, 2)] {
+        let mut bytes = built.bytes.clone();
+        bytes[built.eocd_offset + 8..][..2].copy_from_slice(&declared.to_le_bytes());
+        bytes[built.eocd_offset + 10..][..2].copy_from_slice(&declared.to_le_bytes());
+        let last = piz::Zip::new(&bytes).unwrap().strict_iter().last().unwrap();
+        assert_eq!(last.unwrap_err(), StrictError::RecordCountMismatch { declared: declared.into(), found });
+    }
+}
+
+#[test]
+fn strict_iter_errors() {
+    use piz::StrictError;
+
+    let mut built = Archive::new(vec![
+        Entry::stored("a", b"aaa"),
+        Entry::stored("b", b"bbb"),
+    ]).build_detailed();
+
+    // Filename of the second record runs past the end of the archive
+    built.patch_central16(1, 28, u16::MAX);
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    let results: Vec<_> = zip.strict_iter().map(|file| file.map(|file| file.filename)).collect();
+    assert_eq!(results, [Ok(&b"a"[..]), Err(StrictError::Malformed { offset: built.central_offsets[1] })]);
+    // While the lenient one just ends
+    assert_eq!(zip.central_dir_iter.count(), 1);
+
+    built.patch_central16(1, 28, 1);
+    built.patch_central16(1, 10, 0x1234);
+    let results: Vec<_> = piz::Zip::new(&built.bytes).unwrap().strict_iter().filter_map(Result::err).collect();
+    assert_eq!(results, [StrictError::UnknownCompressionMethod { offset: built.central_offsets[1], method: 0x1234 }]);
+}
+
+#[test]
+fn strict_iter_truncated_data() {
+    use piz::StrictError;
+
+    let big = vec![b'x'; 1000];
+    let built = Archive::new(vec![Entry::stored("a", b"aaa"), Entry::stored("big", &big)]).build_detailed();
+    // Cut the last entry's data down to 10 bytes, keeping the directory after it
+    let data_start = built.local_offsets[1] + 30 + 3;
+    let cut = big.len() - 10;
+    let mut bytes = [&built.bytes[..data_start + 10], &built.bytes[built.central_dir_offset..]].concat();
+    let eocd = bytes.len() - 22;
+    bytes[eocd + 16..eocd + 20].copy_from_slice(&((built.central_dir_offset - cut) as u32).to_le_bytes());
+
+    let zip = piz::Zip::new(&bytes).unwrap();
+    let results: Vec<_> = zip.strict_iter().map(|file| file.map(|file| file.filename)).collect();
+    let available = bytes.len() - data_start;
+    assert_eq!(results, [
+        Ok(&b"a"[..]),
+        Err(StrictError::TruncatedData { offset: built.central_offsets[1] - cut, needed: 1000, available }),
+    ]);
+    // The lenient one lists it, but there's no data to read
+    let last = zip.central_dir_iter.last().unwrap();
+    assert_eq!(last.filename, b"big");
+    assert!(last.bytes().is_none());
+}
+
+#[test]
+fn quick_count() {
+    let mut built = Archive::new(vec![
+        Entry::stored("a", b"a"),
+        Entry::stored("b", b"b"),
+        Entry::stored("c", b"c"),
+    ]).build_detailed();
+    assert_eq!(piz::Zip::new(&built.bytes).unwrap().quick_count(), 3);
+
+    // Doesn't care about local headers
+    built.patch_central32(1, 42, u32::MAX);
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    assert_eq!(zip.quick_count(), 3);
+}
+
+#[test]
+fn len() {
+    let bytes = Archive::new(vec![Entry::stored("a", b"a"), Entry::stored("b", b"b"), Entry::stored("c/", b"")]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+    assert_eq!(zip.len(), 3);
+    assert_eq!(zip.len(), zip.files().count() as u64);
+    assert!(!zip.is_empty());
+    assert!(piz::Zip::new(&Archive::default().build()).unwrap().is_empty());
+}
+
+#[test]
+fn central_record_offset() {
+    let built = Archive::new(vec![Entry::stored("a", b"a"), Entry::stored("b", b"bb"), Entry::stored("c", b"ccc")]).build_detailed();
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    let offsets: Vec<_> = zip.files().map(|file| file.central_record_offset().unwrap()).collect();
+    assert_eq!(offsets, built.central_offsets);
+    assert_eq!(zip.get(2).unwrap().central_record_offset(), Some(built.central_offsets[2]));
+    assert_eq!(zip.strict_iter().nth(1).unwrap().unwrap().central_record_offset(), Some(built.central_offsets[1]));
+
+    let local: Vec<_> = piz::local::LocalHeaderIter::new(&built.bytes).map(|file| file.central_record_offset()).collect();
+    assert_eq!(local, [None, None, None]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn validate() {
+    use piz::{MismatchError, StrictError, ValidationError};
+
+    let entries = vec![
+        Entry::stored("a", b"a"),
+        Entry::stored("b", b"b"),
+        Entry::stored("c", b"c"),
+        Entry::stored("d", b"d"),
+    ];
+    let built = Archive::new(entries.clone()).build_detailed();
+    assert_eq!(piz::Zip::new(&built.bytes).unwrap().validate(), Ok(()));
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.central_offsets[0]] ^= 0xFF;
+    bytes[built.local_offsets[1]] ^= 0xFF;
+    bytes[built.local_offsets[2] + 30] = b'x';
+    // Data runs into the next entry, but that's only noticed through the sizes
+    bytes[built.local_offsets[3] + 18] = 2;
+    let errors = piz::Zip::new(&bytes).unwrap().validate().unwrap_err();
+    assert_eq!(errors, [
+        ValidationError::Directory(StrictError::InvalidSignature { offset: built.central_offsets[0] }),
+        ValidationError::LocalHeader { filename: b"b", error: MismatchError::InvalidLocalSignature { found: 0x04034baf } },
+        ValidationError::LocalHeader { filename: b"c", error: MismatchError::FilenameMismatch },
+        ValidationError::LocalHeader { filename: b"d", error: MismatchError::CompressedSizeMismatch { central: 1, local: Some(2) } },
+    ]);
+
+    // More records than declared
+    let mut built = Archive::new(entries).build_detailed();
+    built.bytes[built.eocd_offset + 8..][..4].copy_from_slice(&[3, 0, 3, 0]);
+    let errors = piz::Zip::new(&built.bytes).unwrap().validate().unwrap_err();
+    assert_eq!(errors, [ValidationError::Directory(StrictError::RecordCountMismatch { declared: 3, found: 4 })]);
+
+    // Broken data descriptors
+    let mut wrong_crc = Entry::stored("wrong", b"w").streamed(true, false);
+    wrong_crc.trailer = common::descriptor(!wrong_crc.crc, 1, 1, true, false);
+    let mut built = Archive::new(vec![
+        Entry::stored("ok", b"o").streamed(true, false),
+        wrong_crc.clone(),
+        Entry::stored("missing", b"m").streamed(true, false),
+    ]).build_detailed();
+    // Data of the last one reaches 4 bytes before the end, leaving no room for a descriptor
+    let data_offset = built.local_offsets[2] + 30 + b"missing".len();
+    let size = built.bytes.len() - 4 - data_offset;
+    built.patch_central32(2, 20, size as u32);
+    let errors = piz::Zip::new(&built.bytes).unwrap().validate().unwrap_err();
+    assert_eq!(errors, [
+        ValidationError::LocalHeader { filename: b"wrong", error: MismatchError::CrcFieldMismatch { central: wrong_crc.crc, other: !wrong_crc.crc } },
+        ValidationError::LocalHeader { filename: b"missing", error: MismatchError::DataDescriptorMissing },
+    ]);
+}
+
+#[test]
+fn lazy_data() {
+    let mut built = Archive::new(vec![
+        Entry::stored("a", b"a"),
+        Entry::stored("b", b"b"),
+        Entry::stored("c", b"c"),
+    ]).build_detailed();
+    built.patch_central32(1, 42, u32::MAX);
+
+    // Unreachable data doesn't stop listing
+    let files: Vec<_> = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.collect();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0].bytes(), Some(&b"a"[..]));
+    assert_eq!(files[1].bytes(), None);
+    assert_eq!(files[1].data_range(), None);
+    assert_eq!(files[2].bytes(), Some(&b"c"[..]));
+}
+
+#[test]
+fn open_options() {
+    use piz::OpenOptions;
+
+    let built = Archive::new(vec![
+        Entry::stored("a", b"aaa"),
+        Entry::stored("b", b"bbb"),
+    ]).build_detailed();
+
+    let max_one = OpenOptions { max_entries: Some(1), ..OpenOptions::default() };
+    assert!(piz::Zip::with_options(&built.bytes, max_one).is_none());
+    let max_two = OpenOptions { max_entries: Some(2), ..OpenOptions::default() };
+    assert!(piz::Zip::with_options(&built.bytes, max_two).is_some());
+
+    let mut flipped = built.bytes.clone();
+    flipped[built.central_offsets[1]] ^= 0xFF;
+    let signatures = OpenOptions { require_valid_signatures: true, ..OpenOptions::default() };
+    assert!(piz::Zip::with_options(&built.bytes, signatures).is_some());
+    assert!(piz::Zip::with_options(&flipped, signatures).is_none());
+    assert!(piz::Zip::new(&flipped).is_some());
+
+    let mut flipped = built.bytes.clone();
+    flipped[built.local_offsets[1]] ^= 0xFF;
+    let local_headers = OpenOptions { require_valid_local_headers: true, ..OpenOptions::default() };
+    assert!(piz::Zip::parse_with_options(&built.bytes, local_headers).is_ok());
+    assert_eq!(piz::Zip::parse_with_options(&flipped, local_headers).err(), Some(piz::ZipError::InvalidLocalHeader { index: 1 }));
+    assert!(piz::Zip::new(&flipped).is_some());
+
+    let commented = Archive { comment: vec![b'c'; 1000], ..Archive::new(vec![Entry::stored("a", b"aaa")]) }.build();
+    for full_scan in [false, true] {
+        let short = OpenOptions { max_comment_scan: Some(999), full_scan, ..OpenOptions::default() };
+        assert_eq!(piz::Zip::parse_with_options(&commented, short).err(), Some(piz::ZipError::EndRecordNotFound));
+        let enough = OpenOptions { max_comment_scan: Some(1000), full_scan, ..OpenOptions::default() };
+        assert_eq!(piz::Zip::parse_with_options(&commented, enough).unwrap().comment().len(), 1000);
+    }
+
+    let mut prefixed = vec![0x90; 4096];
+    prefixed.extend_from_slice(&built.bytes);
+    let zip = piz::Zip::new(&prefixed).unwrap();
+    assert_eq!(zip.prefix_len(), 4096);
+    let contents: Vec<_> = zip.central_dir_iter.map(|file| file.bytes().unwrap()).collect();
+    assert_eq!(contents, [b"aaa", b"bbb"]);
+
+    let no_prefix = OpenOptions { allow_prefix: false, ..OpenOptions::default() };
+    let zip = piz::Zip::with_options(&prefixed, no_prefix).unwrap();
+    assert_eq!(zip.central_dir_iter.count(), 0);
+
+    // Without a prefix there's nothing to skip
+    assert_eq!(piz::Zip::new(&built.bytes).unwrap().prefix_len(), 0);
+}
+
+#[test]
+fn junk_before_end_record_is_not_a_prefix() {
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    let mut bytes = built.bytes[..built.eocd_offset].to_vec();
+    bytes.extend_from_slice(&[0; 16]);
+    bytes.extend_from_slice(&built.bytes[built.eocd_offset..]);
+
+    let mut zip = piz::Zip::new(&bytes).unwrap();
+    assert_eq!(zip.prefix_len(), 0);
+    assert_eq!(zip.central_dir_iter.next().unwrap().bytes().unwrap(), b"aaa");
+}
+
+#[test]
+fn lenient_record_counts() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static ANOMALIES: AtomicUsize = AtomicUsize::new(0);
+
+    fn count(anomaly: piz::Anomaly) {
+        assert!(matches!(anomaly, piz::Anomaly::RecordCountMismatch { used: 3, .. }));
+        ANOMALIES.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let options = piz::OpenOptions { on_anomaly: Some(count), ..piz::OpenOptions::default() };
+    let built = Archive::new(vec![
+        Entry::stored("a", b"a"),
+        Entry::stored("b", b"b"),
+        Entry::stored("c", b"c"),
+    ]).build_detailed();
+
+    // Records on this disk zeroed
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 8..][..2].copy_from_slice(&0u16.to_le_bytes());
+    let zip = piz::Zip::with_options(&bytes, options).unwrap();
+    assert_eq!(zip.central_dir_records_total, 3);
+
+    // Total zeroed
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 10..][..2].copy_from_slice(&0u16.to_le_bytes());
+    let zip = piz::Zip::with_options(&bytes, options).unwrap();
+    assert_eq!(zip.central_dir_records_total, 3);
+    assert_eq!(zip.quick_count(), 3);
+
+    assert_eq!(ANOMALIES.load(Ordering::Relaxed), 2);
+
+    // Consistent counts are not an anomaly
+    piz::Zip::with_options(&built.bytes, options).unwrap();
+    assert_eq!(ANOMALIES.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn comment_length_overrun() {
+    use piz::{OpenOptions, ZipError};
+
+    let mut archive = Archive::new(vec![Entry::stored("a", b"aaa")]);
+    archive.comment = b"0123456789".to_vec();
+    let built = archive.build_detailed();
+    assert_eq!(piz::Zip::new(&built.bytes).unwrap().comment(), b"0123456789");
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 20..][..2].copy_from_slice(&500u16.to_le_bytes());
+    assert!(piz::Zip::new(&bytes).is_none());
+
+    let lenient = OpenOptions { full_scan: true, ..OpenOptions::default() };
+    let zip = piz::Zip::with_options(&bytes, lenient).unwrap();
+    assert_eq!(zip.comment(), b"0123456789");
+    assert_eq!(zip.central_dir_iter.count(), 1);
+
+    let strict = OpenOptions { strict: true, ..lenient };
+    let err = piz::Zip::parse_with_options(&bytes, strict).err();
+    assert_eq!(err, Some(ZipError::CommentLengthExceedsData { declared: 500, available: 10 }));
+}
+
+#[test]
+fn trailing_padding() {
+    for comment in [&b""[..], b"comment"] {
+        let mut archive = Archive::new(vec![Entry::stored("a", b"aaa"), Entry::stored("b", b"bbb")]);
+        archive.comment = comment.to_vec();
+        let mut bytes = archive.build();
+        bytes.extend_from_slice(&[0; 10]);
+
+        let zip = piz::Zip::new(&bytes).unwrap();
+        assert_eq!(zip.comment(), comment);
+        let contents: Vec<_> = zip.central_dir_iter.map(|file| file.bytes().unwrap()).collect();
+        assert_eq!(contents, [b"aaa", b"bbb"]);
+    }
+}
+
+#[test]
+fn longest_comment() {
+    let mut archive = Archive::new(vec![Entry::stored("a", b"aaa")]);
+    archive.comment = vec![b'x'; u16::MAX as usize];
+    let bytes = archive.build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+    assert_eq!(zip.comment().len(), u16::MAX as usize);
+    assert_eq!(zip.central_dir_iter.count(), 1);
+}
+
+#[test]
+fn parse_errors() {
+    use piz::ZipError;
+
+    assert_eq!(piz::Zip::parse(b"PK").err(), Some(ZipError::BufferTooSmall { len: 2 }));
+    assert_eq!(piz::Zip::parse(&[0; 100]).err(), Some(ZipError::EndRecordNotFound));
+
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    assert!(piz::Zip::parse(&built.bytes).is_ok());
+
+    // Saturated central directory offset, but no Zip64 records
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 16..][..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(piz::Zip::parse(&bytes).err(), Some(ZipError::Zip64LocatorMissing));
+    assert_eq!(ZipError::Zip64LocatorMissing.to_string(), "zip64 end of central directory locator not found");
+}
+
+#[test]
+fn split_archive() {
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+
+    // Last disk of a three-part archive, central directory on the first
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 4..][..2].copy_from_slice(&2u16.to_le_bytes());
+    assert_eq!(
+        piz::Zip::parse(&bytes).err(),
+        Some(piz::ZipError::SplitArchive { disk: 2, central_dir_start_disk: 0 })
+    );
+
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 6..][..2].copy_from_slice(&1u16.to_le_bytes());
+    assert_eq!(
+        piz::Zip::parse(&bytes).err(),
+        Some(piz::ZipError::SplitArchive { disk: 0, central_dir_start_disk: 1 })
+    );
+}
+
+#[test]
+fn spanning_signature() {
+    let built = Archive::new(vec![Entry::stored("a", b"aaa")]).build_detailed();
+    let strict = piz::OpenOptions { allow_prefix: false, ..Default::default() };
+
+    for marker in [b"PK\x07\x08", b"PK00"] {
+        let bytes = [&marker[..], &built.bytes].concat();
+        for options in [piz::OpenOptions::default(), strict] {
+            let zip = piz::Zip::parse_with_options(&bytes, options).unwrap();
+            assert_eq!(zip.prefix_len(), 4);
+            assert_eq!(zip.central_dir_iter.map(|file| file.bytes().unwrap()).collect::<Vec<_>>(), [b"aaa"]);
+        }
+    }
+
+    // Segments of a split archive glued together
+    let mut bytes = [&b"PK\x07\x08"[..], &built.bytes].concat();
+    bytes[4 + built.eocd_offset + 4..][..2].copy_from_slice(&1u16.to_le_bytes());
+    assert_eq!(piz::Zip::parse(&bytes).err(), Some(piz::ZipError::SpannedArchive { disk: 1 }));
+}
+
+#[test]
+fn zip64_record_count_only() {
+    let entries = (0..100_000).map(|i| Entry::stored(&format!("{i}"), b"")).collect();
+    let mut built = Archive { zip64: true, ..Archive::new(entries) }.build_detailed();
+    // Only the count is saturated, offset and size fit in the end record
+    let central_dir_size = built.eocd_offset - 56 - 20 - built.central_dir_offset;
+    built.bytes[built.eocd_offset + 12..][..4].copy_from_slice(&(central_dir_size as u32).to_le_bytes());
+    built.bytes[built.eocd_offset + 16..][..4].copy_from_slice(&(built.central_dir_offset as u32).to_le_bytes());
+
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    assert_eq!(zip.central_dir_records_total, 100_000);
+    assert_eq!(zip.files().remaining, 100_000);
+    assert_eq!(zip.central_dir_iter.count(), 100_000);
+}
+
+#[test]
+fn is_zip64() {
+    let entries = vec![Entry::stored("a", b"a"), Entry::stored("b", b"b")];
+    let small = Archive::new(entries.clone()).build();
+    assert!(!piz::Zip::new(&small).unwrap().is_zip64());
+
+    let zip64 = Archive { zip64: true, ..Archive::new(entries.clone()) }.build();
+    assert!(piz::Zip::new(&zip64).unwrap().is_zip64());
+
+    // Only an entry has the extra field, like streaming writers do
+    let mut data = Vec::new();
+    common::put64(&mut data, 1);
+    common::put64(&mut data, 1);
+    let mut entry = Entry::stored("c", b"c");
+    entry.central_extra = common::extra_field(0x0001, &data);
+    let bytes = Archive::new([entries, vec![entry]].concat()).build();
+    assert!(piz::Zip::new(&bytes).unwrap().is_zip64());
+}
+
+#[test]
+fn zip64_end_record() {
+    let entries = (0..70000).map(|i| Entry::stored(&format!("{i}"), b"")).collect();
+    let archive = Archive { zip64: true, ..Archive::new(entries) };
+    let built = archive.build_detailed();
+
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    assert_eq!(zip.central_dir_records_total, 70000);
+    assert_eq!(zip.quick_count(), 70000);
+    assert_eq!(zip.central_dir_iter.last().unwrap().filename, b"69999");
+
+    let mut prefixed = vec![0; 100];
+    prefixed.extend_from_slice(&built.bytes);
+    let options = piz::OpenOptions { allow_prefix: true, ..piz::OpenOptions::default() };
+    let zip = piz::Zip::with_options(&prefixed, options).unwrap();
+    assert_eq!(zip.prefix_len(), 100);
+    assert_eq!(zip.central_dir_iter.count(), 70000);
+
+    // Locator pointing to nowhere, which is only forgiven when looking for a prefix
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset - 12..][..8].copy_from_slice(&1u64.to_le_bytes());
+    assert!(piz::Zip::parse(&bytes).is_ok());
+    let options = piz::OpenOptions { allow_prefix: false, ..piz::OpenOptions::default() };
+    assert_eq!(piz::Zip::parse_with_options(&bytes, options).err(), Some(piz::ZipError::Zip64EndRecordNotFound));
+}
+
+#[test]
+fn by_name() {
+    let bytes = Archive::new(vec![
+        Entry::stored("foobar", b"1"),
+        Entry::stored("dir/foo", b"2"),
+        Entry::stored("foo", b"3"),
+    ]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    assert_eq!(zip.by_name(b"foo").unwrap().bytes().unwrap(), b"3");
+    assert_eq!(zip.by_name(b"dir/foo").unwrap().bytes().unwrap(), b"2");
+    assert!(zip.by_name(b"fo").is_none());
+    assert!(zip.by_name(b"bar").is_none());
+}
+
+#[test]
+fn entries_with_prefix() {
+    let bytes = Archive::new(vec![
+        Entry::stored("src/", b""),
+        Entry::stored("src/lib.rs", b"1"),
+        Entry::stored("srcx/main.rs", b"2"),
+        Entry::stored("README", b"3"),
+        Entry::stored("src/raw/mod.rs", b"4"),
+    ]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    let names: Vec<_> = zip.entries_with_prefix(b"src/").map(|file| file.filename).collect();
+    assert_eq!(names, [&b"src/"[..], b"src/lib.rs", b"src/raw/mod.rs"]);
+    assert_eq!(zip.entries_with_prefix(b"").count(), 5);
+    assert_eq!(zip.entries_with_prefix(b"docs/").count(), 0);
+}
+
+#[test]
+fn by_name_ignore_ascii_case() {
+    let bytes = Archive::new(vec![
+        Entry::stored("Docs/ReadMe.TXT", b"1"),
+        Entry::stored("readme.txt", b"2"),
+    ]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    assert_eq!(zip.by_name_ignore_ascii_case(b"docs/readme.txt").unwrap().bytes().unwrap(), b"1");
+    assert_eq!(zip.by_name_ignore_ascii_case(b"README.TXT").unwrap().bytes().unwrap(), b"2");
+    assert!(zip.by_name(b"README.TXT").is_none());
+}
+
+#[test]
+fn get() {
+    let built = Archive::new(vec![
+        Entry::stored("a", b"1"),
+        Entry::stored("b", b"2"),
+        Entry::stored("c", b"3"),
+    ]).build_detailed();
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    let names: Vec<_> = piz::Zip::new(&built.bytes).unwrap().central_dir_iter.map(|f| f.filename).collect();
+
+    assert_eq!(zip.get(0).unwrap().filename, names[0]);
+    assert_eq!(zip.get(2).unwrap().filename, names[2]);
+    assert!(zip.get(3).is_none());
+
+    // Declared count is respected even if there are more records
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 8..][..2].copy_from_slice(&2u16.to_le_bytes());
+    bytes[built.eocd_offset + 10..][..2].copy_from_slice(&2u16.to_le_bytes());
+    assert!(piz::Zip::new(&bytes).unwrap().get(2).is_none());
+}
+
+#[test]
+fn size_hint() {
+    let built = Archive::new(vec![
+        Entry::stored("a", b"1"),
+        Entry { method: 0x1234, ..Entry::stored("unknown", b"2") },
+        Entry::stored("c", b"3"),
+    ]).build_detailed();
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    let mut iter = zip.central_dir_iter;
+    assert_eq!(iter.remaining, zip.central_dir_records_total);
+    assert_eq!(iter.size_hint(), (0, Some(3)));
+    iter.next();
+    assert_eq!(iter.size_hint(), (0, Some(2)));
+    // The unknown one is skipped, so there's less than the upper bound
+    let rest: Vec<_> = iter.collect();
+    assert_eq!(rest.len(), 1);
+
+    // Records past the declared count are not read
+    let mut bytes = built.bytes.clone();
+    bytes[built.eocd_offset + 8..][..2].copy_from_slice(&2u16.to_le_bytes());
+    bytes[built.eocd_offset + 10..][..2].copy_from_slice(&2u16.to_le_bytes());
+    let iter = piz::Zip::new(&bytes).unwrap().central_dir_iter;
+    assert_eq!(iter.size_hint(), (0, Some(2)));
+    assert_eq!(iter.count(), 1);
+}
+
+#[test]
+fn archive_comment() {
+    let mut archive = Archive::new(vec![Entry::stored("a", b"")]);
+    archive.comment = b"version=1.2.3\nbuild=4567".to_vec();
+    let bytes = archive.build();
+    assert_eq!(piz::Zip::new(&bytes).unwrap().comment(), b"version=1.2.3\nbuild=4567");
+
+    let bytes = Archive::new(vec![Entry::stored("a", b"")]).build();
+    assert_eq!(piz::Zip::new(&bytes).unwrap().comment(), b"");
+}
+
+#[test]
+fn huge_zip64_sizes() {
+    let huge = 5u64 << 30;
+    let mut data = Vec::new();
+    common::put64(&mut data, huge);
+    common::put64(&mut data, huge);
+    let mut entry = Entry::stored("huge", b"not really");
+    entry.central_extra = common::extra_field(0x0001, &data);
+    let mut built = Archive::new(vec![entry, Entry::stored("small", b"ok")]).build_detailed();
+    built.patch_central32(0, 20, u32::MAX);
+    built.patch_central32(0, 24, u32::MAX);
+    let mut zip = piz::Zip::new(&built.bytes).unwrap();
+
+    #[cfg(target_pointer_width = "64")]
+    {
+        let file = zip.central_dir_iter.next().unwrap();
+        assert_eq!(file.uncompressed_size_u64(), huge);
+        assert_eq!(file.decompressed_size, huge as usize);
+        // Data would run way past the buffer
+        assert!(file.bytes().is_none());
+    }
+    #[cfg(target_pointer_width = "32")]
+    {
+        assert_eq!(
+            zip.strict_iter().next().map(|file| file.err()),
+            Some(Some(piz::StrictError::SizeOverflow { offset: built.central_offsets[0], size: huge }))
+        );
+        assert_eq!(zip.central_dir_iter.next().unwrap().filename, b"small");
+    }
+
+    assert_eq!(piz::ZipError::SizeOverflow { value: huge }.to_string(), "5368709120 doesn't fit in usize");
+}
+
+#[test]
+fn empty_archive() {
+    let mut eocd = vec![0x50, 0x4b, 0x05, 0x06];
+    eocd.extend_from_slice(&[0; 18]);
+    assert_eq!(eocd.len(), 22);
+
+    let zip = piz::Zip::new(&eocd).unwrap();
+    assert_eq!(zip.central_dir_records_total, 0);
+    assert!(zip.by_name(b"a").is_none());
+    assert!(zip.get(0).is_none());
+    assert_eq!(zip.strict_iter().count(), 0);
+    assert_eq!(zip.central_dir_iter.count(), 0);
+
+    assert_eq!(piz::Zip::new(&Archive::default().build()).unwrap().central_dir_iter.count(), 0);
+
+    // Offset pointing past the end record doesn't matter when there's nothing to read
+    eocd[16..20].copy_from_slice(&1000u32.to_le_bytes());
+    assert_eq!(piz::Zip::new(&eocd).unwrap().central_dir_iter.count(), 0);
+}
+
+#[test]
+fn exact_size_entries() {
+    let built = Archive::new(vec![
+        Entry::stored("a", b"1"),
+        Entry { method: 0x1234, ..Entry::stored("unknown", b"2") },
+        Entry::stored("c", b"3"),
+    ]).build_detailed();
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    let mut iter = zip.entries();
+    assert_eq!(iter.len() as u64, zip.central_dir_records_total);
+    iter.next();
+    assert_eq!(iter.len(), 2);
+    let mut rest = Vec::with_capacity(iter.len());
+    rest.extend(iter.map(|file| file.map(|file| file.filename)));
+    assert_eq!(rest, [None, Some(&b"c"[..])]);
+
+    // Records after one that doesn't fit still count
+    let mut built = built;
+    built.patch_central16(1, 28, u16::MAX);
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    let names: Vec<_> = zip.entries().map(|file| file.map(|file| file.filename)).collect();
+    assert_eq!(names, [Some(&b"a"[..]), None, None]);
+}
+
+#[test]
+fn files_twice() {
+    let bytes = Archive::new(vec![Entry::stored("a", b"1"), Entry::stored("b", b"2")]).build();
+    let zip = piz::Zip::new(&bytes).unwrap();
+
+    let first: Vec<_> = zip.files().map(|file| file.filename).collect();
+    let second: Vec<_> = zip.files().map(|file| file.filename).collect();
+    assert_eq!(first, [b"a", b"b"]);
+    assert_eq!(first, second);
+    assert_eq!(zip.files().count(), 2);
+}
+
+#[test]
+fn clone_iter_mid_way() {
+    let bytes = Archive::new(vec![
+        Entry::stored("a", b"1"),
+        Entry::stored("b", b"2"),
+        Entry::stored("c", b"3"),
+    ]).build();
+    let mut iter = piz::Zip::new(&bytes).unwrap().central_dir_iter;
+    assert_eq!(iter.next().unwrap().filename, b"a");
+
+    let snapshot = iter;
+    let rest: Vec<_> = iter.map(|file| file.filename).collect();
+    let again: Vec<_> = snapshot.map(|file| file.filename).collect();
+    assert_eq!(rest, [b"b", b"c"]);
+    assert_eq!(rest, again);
+    assert!(format!("{snapshot:?}").starts_with("NonStrictIter { offset: "));
+}
+
+#[test]
+fn central_directory_range() {
+    let built = Archive::new(vec![Entry::stored("a", b"1"), Entry::stored("b", b"2")]).build_detailed();
+    let zip = piz::Zip::new(&built.bytes).unwrap();
+    assert_eq!(zip.central_directory_range(), built.central_dir_offset..built.eocd_offset);
+
+    // Download cut in the middle of the directory, with the end record still there
+    let truncated = [&built.bytes[..built.central_offsets[1]], &built.bytes[built.eocd_offset..]].concat();
+    let error = piz::ZipError::TruncatedDirectory { end: built.eocd_offset as u64, len: truncated.len() };
+    assert_eq!(piz::Zip::parse(&truncated).err(), Some(error));
+}
+
+#[test]
+fn debug_summary() {
+    let built = Archive::new(vec![Entry::stored("a", b"a"), Entry::stored("b", b"b")]).build_detailed();
+    let debug = format!("{:?}", piz::Zip::new(&built.bytes).unwrap());
+    assert!(debug.contains("central_dir_records_total: 2"), "{debug}");
+    assert!(debug.contains(&format!("central_dir_offset: {}", built.central_dir_offset)), "{debug}");
+    assert!(debug.contains("zip64: false"), "{debug}");
+}
+
+#[test]
+fn strict_iter_wrong_directory_offset() {
+    use piz::StrictError;
+
+    let mut built = Archive::new(vec![
+        Entry::stored("a", b"aaa"),
+        Entry::stored("b", b"bbb"),
+    ]).build_detailed();
+    // Points into the middle of the first local header
+    built.bytes[built.eocd_offset + 16..][..4].copy_from_slice(&4u32.to_le_bytes());
+    let zip = piz::Zip::parse_with_options(&built.bytes, piz::OpenOptions { allow_prefix: false, ..Default::default() }).unwrap();
+    let results: Vec<_> = zip.strict_iter().map(|file| file.map(|file| file.filename)).collect();
+    assert_eq!(results, [Err(StrictError::InvalidSignature { offset: 4 })]);
+}